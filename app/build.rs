@@ -2,7 +2,7 @@ use std::process::Command;
 
 fn main() {
     let out_dir = std::env::var("OUT_DIR").unwrap();
-    let shaders = &["basic", "light"];
+    let shaders = &["basic", "light", "skinned"];
 
     for shader in shaders {
         let shader_path = format!("src/shaders/{shader}.nzsl");
@@ -1,33 +1,99 @@
-use std::f32::consts::TAU;
-
-use glam::{Mat4, Quat, Vec3, vec3};
-use lux::App as _;
+use std::{f32::consts::TAU, ops::Range};
+
+use glam::{Mat4, Quat, Vec2, Vec3, vec3, vec4};
+use lux::{
+    anim::{Animation, Easing, Interpolation, PlayMode, Playback, Track},
+    asset::{AssetServer, Handle},
+    buffer::DynamicBuffer,
+    camera::{
+        controller::{self, FlyController, FpsController, OrbitController},
+        Camera, CameraUniform, DepthMode, Projection,
+    },
+    culling::CullStats,
+    ecs::{
+        hierarchy::{update_global_transforms, world_matrix, Parent},
+        picking,
+        render::{MeshInstance, RenderView, ViewCamera},
+        scene::{SceneFile, SceneInstance},
+        Entity, Schedule, World,
+    },
+    graph::{RenderGraph, TextureDesc, TransientPool},
+    input::Input,
+    light::{Light, LightKind, LightList, MAX_LIGHTS},
+    material::{Material, MaterialId, MaterialLibrary},
+    mesh::{primitives, GpuMesh, Mesh, VertexData},
+    particles::{EmitterShape, ParticleEmitter, ParticleSystem},
+    physics::{BodyKind, Physics, RigidBody},
+    post::{Bloom, Fxaa, PostProcessChain, Vignette, HDR_FORMAT},
+    shadow::PointShadowMap,
+    skin::{AnimationClip, Channel, ChannelValues, ClipLayer, GpuSkin, Joint, JointPalette, Skeleton, SkinVertex},
+    texture::{ColorSpace, Texture},
+    transform::Transform,
+    App as _,
+};
 use lux_derive::HotReload;
 use rand::Rng;
-use wgpu::{include_spirv, util::DeviceExt};
-use winit::{dpi::PhysicalSize, window::Window};
+use wgpu::include_spirv;
+use winit::{
+    dpi::PhysicalSize,
+    event::{MouseButton, VirtualKeyCode, WindowEvent},
+    window::Window,
+};
 
 #[derive(HotReload)]
 pub struct App {
     render_device: RenderDevice,
+    assets: AssetServer,
     size: winit::dpi::PhysicalSize<u32>,
-    render_pipeline: wgpu::RenderPipeline,
-    depth_texture: wgpu::Texture,
+    render_pipeline_layout: wgpu::PipelineLayout,
+    skinned_render_pipeline_layout: wgpu::PipelineLayout,
+    pipelines: ScenePipelines,
+    transient_pool: TransientPool,
+    post_chain: PostProcessChain,
+    input: Input,
 
     time: f32,
 
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
-
-    cubes: Vec<Cube>,
-    cube_mesh: GpuMesh,
-    cubes_instance_buffer: wgpu::Buffer,
-
-    light_render_pipeline: wgpu::RenderPipeline,
-    light_buffer: wgpu::Buffer,
-    light_bind_group: wgpu::BindGroup,
-    light_pos: Vec3,
-    light_color: Vec3,
+    // Les entités et le matériau suivants n'existent qu'une fois la scène chargée
+    camera: Option<Entity>,
+
+    world: World,
+    // Le fichier est surveillé, les entités sont recréées quand sa version change
+    scene: Handle<SceneFile>,
+    scene_version: u32,
+    scene_instance: SceneInstance,
+    schedule: Schedule,
+    physics: Physics,
+    cube_mesh: Handle<GpuMesh>,
+    instance_buffer: DynamicBuffer<InstanceData>,
+    mesh_draws: Vec<MeshDraw>,
+    // Journalisé seulement quand il change, pour ne pas écrire à chaque frame
+    cull_stats: CullStats,
+    materials: MaterialLibrary,
+    floor_material: Option<MaterialId>,
+    floor_albedo: Handle<Texture>,
+    floor_albedo_version: u32,
+    floor_material_bind_group: wgpu::BindGroup,
+    cube_material_bind_group: wgpu::BindGroup,
+
+    light_gizmos_buffer: wgpu::Buffer,
+    light_gizmo_count: u32,
+    lights: LightList,
+    // Le dépassement de `MAX_LIGHTS` n'est signalé qu'une fois
+    lights_overflow_warned: bool,
+    orbit_light: Option<Entity>,
+    sparks: ParticleSystem,
+    shadow_map: PointShadowMap,
+
+    skinned_camera_bind_group: wgpu::BindGroup,
+    tentacle_mesh: GpuMesh,
+    tentacle_skin: GpuSkin,
+    tentacle_skeleton: Skeleton,
+    tentacle_clips: Vec<AnimationClip>,
+    tentacle_palette: JointPalette,
+    tentacle_instance_buffer: wgpu::Buffer,
 }
 
 impl lux::App for App {
@@ -35,6 +101,7 @@ impl lux::App for App {
         let size = window.inner_size();
         let render_device = pollster::block_on(RenderDevice::new(&window));
         let device = &render_device.device;
+        let queue = &render_device.queue;
 
         let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Camera Buffer"),
@@ -67,181 +134,385 @@ impl lux::App for App {
             }],
         });
 
-        let light_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Light Buffer"),
-            size: std::mem::size_of::<LightUniform>() as wgpu::BufferAddress,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+        // Pour le shader skinné, la palette d'articulations partage le groupe de la caméra
+        let tentacle_palette = JointPalette::new(device);
 
-        let light_bind_group_layout =
+        let skinned_camera_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Light Bind Group Layout"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
+                label: Some("Skinned Camera Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
                     },
-                    count: None,
-                }],
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
             });
 
-        let light_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Light Bind Group"),
-            layout: &light_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: light_buffer.as_entire_binding(),
-            }],
+        let skinned_camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Skinned Camera Bind Group"),
+            layout: &skinned_camera_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: tentacle_palette.buffer().as_entire_binding(),
+                },
+            ],
         });
 
+        let lights = LightList::new(device);
+
+        let light_gizmos_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Light Gizmos Buffer"),
+            size: (MAX_LIGHTS * std::mem::size_of::<LightGizmoData>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let shadow_map = PointShadowMap::new(device, 1024, &[VertexData::desc(), InstanceData::desc()]);
+
+        // Étincelles qui suivent la lumière en orbite, assez lumineuses pour le bloom
+        let sparks = ParticleSystem::new(
+            device,
+            ParticleEmitter::default()
+                .with_shape(EmitterShape::Sphere { radius: 0.15 })
+                .with_rate(300.0)
+                .with_lifetime(0.4, 1.2)
+                .with_velocity(Vec3::ZERO, 3.0)
+                .with_acceleration(vec3(0.0, -6.0, 0.0))
+                .with_drag(1.0)
+                .with_size(0.05, 0.0)
+                .with_color(vec4(8.0, 4.0, 1.0, 1.0), vec4(4.0, 0.5, 0.1, 0.0)),
+            1024,
+            HDR_FORMAT,
+            DepthMode::Standard,
+        );
+
+        let mut materials = MaterialLibrary::new(device, queue);
+
+        let mut assets = AssetServer::new(concat!(env!("CARGO_MANIFEST_DIR"), "/assets"));
+
+        // Chargée en arrière-plan, le bind group est recréé dans `update` une fois prête
+        let floor_albedo = assets.load_with::<Texture>("floor_albedo.png", ColorSpace::Srgb);
+        let floor_material_bind_group = materials.create_bind_group(device, None, None);
+        let cube_normal = build_bevel_normal_texture(device, queue);
+        let cube_material_bind_group = materials.create_bind_group(device, None, Some(&cube_normal));
+
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Render Pipeline Layout"),
                 bind_group_layouts: &[
                     &camera_bind_group_layout,
-                    &light_bind_group_layout,
+                    lights.bind_group_layout(),
+                    shadow_map.bind_group_layout(),
+                    materials.bind_group_layout(),
                 ],
                 push_constant_ranges: &[],
             });
 
-        let render_pipeline = create_render_pipeline(
-            device,
-            &render_pipeline_layout,
-            render_device.config.format,
-            &[VertexData::desc(), InstanceData::desc()],
-            include_spirv!(concat!(env!("OUT_DIR"), "/basic.spv")),
-        );
+        let skinned_render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Skinned Render Pipeline Layout"),
+                bind_group_layouts: &[
+                    &skinned_camera_bind_group_layout,
+                    lights.bind_group_layout(),
+                    shadow_map.bind_group_layout(),
+                    materials.bind_group_layout(),
+                ],
+                push_constant_ranges: &[],
+            });
 
-        let light_render_pipeline = create_render_pipeline(
+        let pipelines = ScenePipelines::new(
             device,
             &render_pipeline_layout,
-            render_device.config.format,
-            &[VertexData::desc()],
-            include_spirv!(concat!(env!("OUT_DIR"), "/light.spv")),
+            &skinned_render_pipeline_layout,
+            DepthMode::Standard,
         );
 
-        let mut rng = rand::thread_rng();
-        let mut cubes: Vec<_> = (0..10)
-            .map(|_| Cube {
-                position: Vec3::ZERO,
-                rotation: Quat::IDENTITY,
-                velocity: Vec3::ZERO,
-                target_position: Vec3::ZERO,
-                rotation_delta: Quat::from_rotation_x(rng.gen_range(0.01..0.03))
-                    * Quat::from_rotation_y(rng.gen_range(0.01..0.03)),
-            })
-            .collect();
+        let cube_mesh = assets.add(GpuMesh::new(&primitives::cube(Vec3::ONE, 1), device));
+        let mut world = World::new();
+
+        // Construit par le code, il est posé sur la base décrite dans le fichier de scène
+        let tentacle = world.spawn();
+        world.insert(tentacle, Transform::from_translation(Vec3::Y * 0.1));
+        world.insert(tentacle, Tentacle {
+            material: materials.add(Material::pbr(vec3(0.8, 0.3, 0.5), 0.0, 0.4)).unwrap(),
+        });
 
-        compute_target_positions(&mut cubes);
+        // Les entités sont créées par `reload_scene` dès que la première version est chargée
+        let scene = assets.load::<SceneFile>("scene.ron");
 
-        let instance_data_size = std::mem::size_of::<InstanceData>();
-        let cubes_instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Cubes Instance Buffer"),
-            size: (cubes.len() * instance_data_size) as wgpu::BufferAddress,
+        let schedule = Schedule::new()
+            .with_system(update_cubes)
+            .with_system(update_orbit_light);
+
+        // Grandit tout seul si des entités sont ajoutées
+        let instance_buffer = DynamicBuffer::new(device, "Instance Buffer", wgpu::BufferUsages::VERTEX, 16);
+
+        let (tentacle, tentacle_skin) = build_tentacle();
+        let tentacle_mesh = GpuMesh::new(&tentacle, device);
+        let tentacle_skin = GpuSkin::new(&tentacle_skin, device);
+        let tentacle_skeleton = build_tentacle_skeleton();
+        let tentacle_clips = build_tentacle_clips();
+
+        let tentacle_instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Tentacle Instance Buffer"),
+            size: std::mem::size_of::<InstanceData>() as wgpu::BufferAddress,
             usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
             mapped_at_creation: false,
         });
 
-        let cube_mesh = GpuMesh::new(&build_cube_mesh(), &device);
-        let depth_texture = create_depth_texture(&device, size.width, size.height);
+        let transient_pool = TransientPool::new(size.width, size.height);
+
+        let post_chain = PostProcessChain::new(device, render_device.config.format, size.width, size.height)
+            .with(Bloom::new(device))
+            .with(Fxaa::new(device))
+            .with(Vignette::new(device));
 
         Self {
             render_device,
+            assets,
             size,
-            render_pipeline,
-            depth_texture,
+            render_pipeline_layout,
+            skinned_render_pipeline_layout,
+            pipelines,
+            transient_pool,
+            post_chain,
+            input: Input::new(),
             time: 0.0,
-            cubes,
+            world,
+            scene,
+            scene_version: 0,
+            scene_instance: SceneInstance::new(),
+            schedule,
+            physics: Physics::new(),
             cube_mesh,
-            cubes_instance_buffer,
+            instance_buffer,
+            mesh_draws: Vec::new(),
+            cull_stats: CullStats::default(),
+            materials,
+            floor_material: None,
+            floor_albedo,
+            floor_albedo_version: 0,
+            floor_material_bind_group,
+            cube_material_bind_group,
             camera_buffer,
             camera_bind_group,
-            light_render_pipeline,
-            light_buffer,
-            light_bind_group,
-            light_pos: Vec3::ZERO,
-            light_color: Vec3::ONE,
+            camera: None,
+            light_gizmos_buffer,
+            light_gizmo_count: 0,
+            lights,
+            lights_overflow_warned: false,
+            orbit_light: None,
+            sparks,
+            shadow_map,
+            skinned_camera_bind_group,
+            tentacle_mesh,
+            tentacle_skin,
+            tentacle_skeleton,
+            tentacle_clips,
+            tentacle_palette,
+            tentacle_instance_buffer,
         }
     }
 
     fn update(&mut self) {
-        // Update camera
-        let camera_pos = Vec3::new(
-            (self.time * 0.1).cos() * 10.0,
-            2.0,
-            (self.time * 0.1).sin() * 10.0,
-        );
+        self.assets.update(&self.render_device.device, &self.render_device.queue);
+
+        // La texture du sol est rechargée quand le fichier change
+        let floor_albedo_version = self.assets.version(&self.floor_albedo);
+        if floor_albedo_version != self.floor_albedo_version {
+            self.floor_albedo_version = floor_albedo_version;
+            self.floor_material_bind_group = self.materials.create_bind_group(
+                &self.render_device.device,
+                self.assets.get(&self.floor_albedo),
+                None,
+            );
+        }
+
+        self.reload_scene();
+        self.save_scene();
+
+        let dt = 1.0 / 60.0;
+        self.switch_camera_controller();
+        self.switch_camera_projection();
+
+        controller::update_controllers::<OrbitController>(&self.world, &self.input, dt);
+        controller::update_controllers::<FlyController>(&self.world, &self.input, dt);
+        controller::update_controllers::<FpsController>(&self.world, &self.input, dt);
+
+        // R relâche les cubes
+        if self.input.key_pressed(VirtualKeyCode::R) {
+            drop_cubes(&mut self.world);
+        }
+
+        self.schedule.run(&mut self.world, dt);
+        self.physics.step(&mut self.world, dt);
+        update_global_transforms(&mut self.world);
 
-        let view_matrix = Mat4::look_at_rh(camera_pos, Vec3::new(0.0, 0.0, 0.0), Vec3::Y);
         let aspect_ratio = self.size.width as f32 / self.size.height as f32;
-        let proj_matrix = Mat4::perspective_rh(60.0f32.to_radians(), aspect_ratio, 0.1, 100.0);
-        let view_proj = (proj_matrix * view_matrix).to_cols_array();
+        let mut view = RenderView::extract(&self.world, aspect_ratio);
+        let cull_stats = view.cull(&self.assets);
 
-        let camera_uniform = CameraUniform {
-            view_proj_matrix: view_proj,
-            pos: camera_pos.into(),
-            _pad: 0,
-        };
+        if cull_stats != self.cull_stats {
+            log::debug!("{} of {} instances visible", cull_stats.visible, cull_stats.total());
+            self.cull_stats = cull_stats;
+        }
 
-        self.render_device.queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[camera_uniform]));
+        if let Some(camera) = view.camera {
+            self.select_cube(&camera);
+            self.render_device.queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[camera.uniform()]));
+
+            // Le test de profondeur est fixé dans les pipelines
+            if camera.depth_mode != self.pipelines.depth_mode {
+                self.pipelines = ScenePipelines::new(
+                    &self.render_device.device,
+                    &self.render_pipeline_layout,
+                    &self.skinned_render_pipeline_layout,
+                    camera.depth_mode,
+                );
+                self.sparks.set_depth_mode(&self.render_device.device, camera.depth_mode);
+            }
+        }
 
-        // Update cubes
-        for cube in &mut self.cubes {
-            let acceleration = (cube.target_position - cube.position) * 0.001 - cube.velocity * 0.03;
-            cube.velocity += acceleration;
-            cube.position += cube.velocity;
-            cube.rotation *= cube.rotation_delta;
+        // Les instances d'un même mesh se suivent dans le buffer, celles du sol
+        // à part car elles utilisent un autre bind group de textures. Dans chaque
+        // groupe les visibles sont au début, les autres ne servent qu'aux ombres.
+        let mut instances = Vec::with_capacity(view.instance_count());
+        self.mesh_draws.clear();
+
+        for batch in &view.batches {
+            let (floor, others): (Vec<&MeshInstance>, Vec<_>) = batch
+                .instances
+                .iter()
+                .partition(|instance| Some(instance.material) == self.floor_material);
+
+            for (mut group, is_floor) in [(floor, true), (others, false)] {
+                if group.is_empty() {
+                    continue;
+                }
+
+                group.sort_by_key(|instance| !instance.visible);
+                let visible_count = group.iter().filter(|instance| instance.visible).count() as u32;
+
+                let start = instances.len() as u32;
+                instances.extend(group.iter().map(|instance| InstanceData {
+                    model_matrix: instance.model_matrix.to_cols_array(),
+                    material_index: instance.material.index(),
+                }));
+
+                self.mesh_draws.push(MeshDraw {
+                    mesh: batch.mesh.clone(),
+                    instances: start..instances.len() as u32,
+                    visible: start..start + visible_count,
+                    floor: is_floor,
+                });
+            }
         }
 
-        let mut cubes_instance_buffer_data: Vec<[f32; 16]> = self.cubes
-            .iter()
-            .map(|cube| (Mat4::from_translation(cube.position) * Mat4::from_quat(cube.rotation)).to_cols_array())
-            .collect();
+        self.instance_buffer.update(&self.render_device.device, &self.render_device.queue, &instances);
 
-        let floor_model_matrix =
-            Mat4::from_translation(vec3(0.0, -2.0, 0.0)) * Mat4::from_scale(vec3(10.0, 0.2, 10.0));
+        let mut tentacle_instance = None;
+        self.world.query::<&Tentacle>(|entity, tentacle| {
+            tentacle_instance = world_matrix(&self.world, entity).map(|model_matrix| InstanceData {
+                model_matrix: model_matrix.to_cols_array(),
+                material_index: tentacle.material.index(),
+            });
+        });
 
-        // Le premier cube c'est en fait le sol mdr
-        cubes_instance_buffer_data[0] = floor_model_matrix.to_cols_array();
+        if let Some(tentacle_instance) = tentacle_instance {
+            self.render_device.queue.write_buffer(
+                &self.tentacle_instance_buffer,
+                0,
+                bytemuck::cast_slice(&[tentacle_instance]),
+            );
+        }
 
-        self.render_device.queue.write_buffer(
-            &self.cubes_instance_buffer,
-            0,
-            bytemuck::cast_slice(&cubes_instance_buffer_data),
-        );
+        // La liste est reconstruite à chaque frame à partir des entités
+        self.lights.clear();
+        let mut shadow_caster = None;
 
-        // Move light
-        let inner_radius = 1.2;
-        let outer_radius = 4.5;
-        let inner_angle = self.time * 5.0;
-        let outer_angle = self.time * 1.0;
-        let (si, ci) = f32::sin_cos(inner_angle);
-        let (so, co) = f32::sin_cos(outer_angle);
-
-        self.light_pos = vec3(
-            co * (ci * inner_radius + outer_radius),
-            si * inner_radius,
-            so * (ci * inner_radius + outer_radius),
-        );
+        for &(entity, light) in &view.lights {
+            let id = self.lights.add(light);
 
-        let light_uniform = LightUniform {
-            pos: self.light_pos.into(),
-            _pad0: 0,
-            color: self.light_color.into(),
-            _pad1: 0,
-        };
+            if id.is_none() && !self.lights_overflow_warned {
+                log::warn!("more than {MAX_LIGHTS} lights in the scene, the extra ones are not drawn");
+                self.lights_overflow_warned = true;
+            }
+
+            if Some(entity) == self.orbit_light {
+                shadow_caster = id.and_then(|id| self.lights.index_of(id)).map(|index| (index, light.position));
+            }
+        }
+
+        self.lights.upload(&self.render_device.queue);
+        self.materials.upload(&self.render_device.queue);
+
+        if let Some((index, position)) = shadow_caster {
+            self.shadow_map.update(&self.render_device.queue, position, index);
+            self.sparks.emitter.position = position;
+        } else {
+            self.shadow_map.disable(&self.render_device.queue);
+        }
 
+        if let Some(camera) = view.camera {
+            self.sparks.update(&self.render_device.queue, &camera.uniform(), dt);
+        }
+
+        // Un petit cube par lumière, sauf pour les directionnelles qui n'ont pas de position
+        let light_gizmos: Vec<LightGizmoData> = self.lights
+            .iter()
+            .filter(|(_, light)| light.kind != LightKind::Directional)
+            .map(|(_, light)| LightGizmoData {
+                model_matrix: (Mat4::from_translation(light.position) * Mat4::from_scale(Vec3::splat(0.2)))
+                    .to_cols_array(),
+                color: light.color.extend(1.0).into(),
+            })
+            .collect();
+
+        self.light_gizmo_count = light_gizmos.len() as u32;
         self.render_device.queue.write_buffer(
-            &self.light_buffer,
+            &self.light_gizmos_buffer,
             0,
-            bytemuck::cast_slice(&[light_uniform]),
+            bytemuck::cast_slice(&light_gizmos),
         );
 
-        self.time += 1.0 / 60.0;
+        // Le tentacule passe progressivement d'un clip à l'autre
+        let sway_weight = (self.time * 0.4).sin() * 0.5 + 0.5;
+        let layers: Vec<ClipLayer> = self.tentacle_clips
+            .iter()
+            .zip([sway_weight, 1.0 - sway_weight])
+            .map(|(clip, weight)| ClipLayer {
+                clip,
+                time: clip.wrap_time(self.time),
+                weight,
+            })
+            .collect();
+
+        let pose = self.tentacle_skeleton.evaluate(&layers);
+        self.tentacle_palette.update(&self.render_device.queue, &self.tentacle_skeleton.palette(&pose));
+
+        self.time += dt;
+        self.input.end_frame();
 
         self.render();
     }
@@ -252,12 +523,134 @@ impl lux::App for App {
             self.render_device.config.width = width;
             self.render_device.config.height = height;
             self.render_device.surface.configure(&self.render_device.device, &self.render_device.config);
-            self.depth_texture = create_depth_texture(&self.render_device.device, width, height);
+            self.transient_pool.resize(width, height);
+            self.post_chain.resize(&self.render_device.device, width, height);
         }
     }
+
+    fn on_window_event(&mut self, event: &WindowEvent) {
+        self.input.handle_event(event);
+    }
 }
 
 impl App {
+    // Un clic gauche sélectionne le cube sous le curseur, ailleurs il
+    // désélectionne
+    fn select_cube(&mut self, camera: &ViewCamera) {
+        if !self.input.button_pressed(MouseButton::Left) {
+            return;
+        }
+
+        let Some(cursor) = self.input.cursor_position() else {
+            return;
+        };
+
+        let viewport_size = Vec2::new(self.size.width as f32, self.size.height as f32);
+        let ray = camera.screen_ray(cursor, viewport_size);
+        let hit = picking::raycast(&self.world, &self.assets, &ray);
+
+        let mut previous = Vec::new();
+        self.world.query::<&Selected>(|entity, _| previous.push(entity));
+        for entity in previous {
+            self.world.remove::<Selected>(entity);
+        }
+
+        if let Some(hit) = hit.filter(|hit| self.world.has::<Cube>(hit.entity)) {
+            self.world.insert(hit.entity, Selected);
+        }
+    }
+
+    fn reload_scene(&mut self) {
+        let scene_version = self.assets.version(&self.scene);
+        if scene_version == self.scene_version {
+            return;
+        }
+        self.scene_version = scene_version;
+
+        let Some(file) = self.assets.get(&self.scene).cloned() else {
+            return;
+        };
+
+        let result = spawn_scene(
+            &file,
+            &mut self.scene_instance,
+            &mut self.world,
+            &mut self.assets,
+            &mut self.materials,
+            &self.render_device.device,
+        );
+
+        match result {
+            Ok(entities) => {
+                self.camera = Some(entities.camera);
+                self.orbit_light = Some(entities.orbit_light);
+                self.floor_material = Some(entities.floor_material);
+            }
+            // La scène précédente reste en place
+            Err(error) => log::error!("{error}"),
+        }
+    }
+
+    // F5 enregistre l'état actuel de la scène à côté du fichier d'origine
+    fn save_scene(&mut self) {
+        if !self.input.key_pressed(VirtualKeyCode::F5) {
+            return;
+        }
+
+        let file = self.scene_instance.to_file(&self.world, &self.materials);
+
+        if let Err(error) = file.save(self.assets.root().join("saved_scene.ron")) {
+            log::error!("failed to save the scene: {error}");
+        }
+    }
+
+    // P passe de la perspective à la perspective infinie puis à l'orthographique
+    fn switch_camera_projection(&mut self) {
+        if !self.input.key_pressed(VirtualKeyCode::P) {
+            return;
+        }
+
+        if let Some(camera) = self.camera.and_then(|camera| self.world.get_mut::<Camera>(camera)) {
+            *camera = match camera.projection {
+                Projection::Perspective { fov_y, near, .. } => Camera::reverse_z_infinite(fov_y, near),
+                Projection::ReverseZInfinite { .. } => Camera::orthographic(12.0, 0.1, 100.0),
+                Projection::Orthographic { .. } => Camera::default(),
+            };
+        }
+    }
+
+    // Le nouveau contrôleur part de la position actuelle de la caméra
+    fn switch_camera_controller(&mut self) {
+        let Some(camera) = self.camera else {
+            return;
+        };
+
+        let Some(transform) = self.world.get::<Transform>(camera).map(|transform| *transform) else {
+            return;
+        };
+
+        let keys = [VirtualKeyCode::Key1, VirtualKeyCode::Key2, VirtualKeyCode::Key3];
+        let Some(switch_to) = keys.into_iter().find(|&key| self.input.key_pressed(key)) else {
+            return;
+        };
+
+        self.world.remove::<OrbitController>(camera);
+        self.world.remove::<FlyController>(camera);
+        self.world.remove::<FpsController>(camera);
+
+        match switch_to {
+            VirtualKeyCode::Key1 => {
+                self.world.insert(camera, OrbitController::from_transform(&transform, 10.0));
+            }
+            VirtualKeyCode::Key2 => {
+                self.world.insert(camera, FlyController::from_transform(&transform));
+            }
+            _ => {
+                self.world.insert(camera, FpsController::from_transform(&transform));
+            }
+        }
+    }
+
     fn render(&mut self) {
         let output = self.render_device.surface.get_current_texture().unwrap();
 
@@ -272,78 +665,233 @@ impl App {
                     label: Some("Render Encoder"),
                 });
 
-        {
-            let depth_texture_view = self
-                .depth_texture
-                .create_view(&wgpu::TextureViewDescriptor::default());
-
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.005,
-                            g: 0.005,
-                            b: 0.005,
-                            a: 1.0,
+        let mut graph = RenderGraph::new();
+
+        let surface = graph.import_texture("Surface", &view);
+        let shadow_map = graph.import_external("Shadow Map");
+        let scene_color = graph.create_texture(TextureDesc::new("Scene Color", HDR_FORMAT));
+        let scene_depth = graph.create_texture(TextureDesc::new("Scene Depth", wgpu::TextureFormat::Depth32Float));
+        let sparks = graph.import_external("Sparks");
+
+        // Ombres : la lumière est au centre de son propre cube, il ne projette donc rien
+        let shadow_map = graph.add_pass(
+            "Shadows",
+            |pass| pass.write(shadow_map),
+            |ctx| {
+                for face in 0..6 {
+                    let mut shadow_pass = self.shadow_map.begin_face_pass(ctx.encoder, face);
+
+                    for draw in &self.mesh_draws {
+                        let Some(mesh) = self.assets.get(&draw.mesh) else {
+                            continue;
+                        };
+
+                        mesh.set_buffers(&mut shadow_pass);
+                        shadow_pass.set_vertex_buffer(1, self.instance_buffer.buffer().slice(..));
+                        mesh.draw(&mut shadow_pass, draw.instances.clone());
+                    }
+                }
+            },
+        );
+
+        // Ne fait rien si les particules sont simulées sur le CPU
+        let sparks = graph.add_pass(
+            "Particles",
+            |pass| pass.write(sparks),
+            |ctx| self.sparks.simulate(ctx.encoder),
+        );
+
+        let lit_scene = graph.add_pass(
+            "Scene",
+            |pass| {
+                pass.read(shadow_map);
+                pass.read(sparks);
+                pass.write(scene_depth);
+                pass.write(scene_color)
+            },
+            |ctx| {
+                let mut render_pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Render Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: ctx.texture(scene_color),
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color {
+                                r: 0.005,
+                                g: 0.005,
+                                b: 0.005,
+                                a: 1.0,
+                            }),
+                            store: true,
+                        },
+                    })],
+                    depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                        view: ctx.texture(scene_depth),
+                        depth_ops: Some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(self.pipelines.depth_mode.clear_value()),
+                            store: true,
                         }),
-                        store: true,
-                    },
-                })],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &depth_texture_view,
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: true,
+                        stencil_ops: None,
                     }),
-                    stencil_ops: None,
-                }),
-            });
+                });
 
-            render_pass.set_vertex_buffer(0, self.cube_mesh.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(
-                self.cube_mesh.index_buffer.slice(..),
-                wgpu::IndexFormat::Uint32,
-            );
-            render_pass.set_vertex_buffer(1, self.cubes_instance_buffer.slice(..));
-            render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
-            render_pass.set_bind_group(1, &self.light_bind_group, &[]);
+                render_pass.set_pipeline(&self.pipelines.render);
+                render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+                render_pass.set_bind_group(1, self.lights.bind_group(), &[]);
+                render_pass.set_bind_group(2, self.shadow_map.bind_group(), &[]);
 
-            // Cubes
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.draw_indexed(0..self.cube_mesh.index_count, 0, 0..self.cubes.len() as _);
+                for draw in &self.mesh_draws {
+                    let Some(mesh) = self.assets.get(&draw.mesh) else {
+                        continue;
+                    };
 
-            // Lumière
-            render_pass.set_pipeline(&self.light_render_pipeline);
-            render_pass.draw_indexed(0..self.cube_mesh.index_count, 0, 0..1);
-        }
+                    // Les textures changent entre le sol et le reste
+                    let material_bind_group = if draw.floor {
+                        &self.floor_material_bind_group
+                    } else {
+                        &self.cube_material_bind_group
+                    };
+
+                    render_pass.set_bind_group(3, material_bind_group, &[]);
+                    mesh.set_buffers(&mut render_pass);
+                    render_pass.set_vertex_buffer(1, self.instance_buffer.buffer().slice(..));
+                    mesh.draw(&mut render_pass, draw.visible.clone());
+                }
+
+                // Tentacule, il ne projette pas d'ombre car le shader d'ombre n'est pas skinné
+                render_pass.set_pipeline(&self.pipelines.skinned);
+                render_pass.set_bind_group(0, &self.skinned_camera_bind_group, &[]);
+                render_pass.set_bind_group(3, self.materials.bind_group(), &[]);
+                self.tentacle_mesh.set_buffers(&mut render_pass);
+                render_pass.set_vertex_buffer(1, self.tentacle_instance_buffer.slice(..));
+                render_pass.set_vertex_buffer(2, self.tentacle_skin.vertex_buffer.slice(..));
+                self.tentacle_mesh.draw(&mut render_pass, 0..1);
+
+                // Lumières
+                if let Some(cube_mesh) = self.assets.get(&self.cube_mesh) {
+                    render_pass.set_pipeline(&self.pipelines.light);
+                    render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+                    cube_mesh.set_buffers(&mut render_pass);
+                    render_pass.set_vertex_buffer(1, self.light_gizmos_buffer.slice(..));
+                    cube_mesh.draw(&mut render_pass, 0..self.light_gizmo_count);
+                }
+
+                // En dernier, elles ne cachent rien mais sont cachées par la scène
+                self.sparks.draw(&mut render_pass);
+            },
+        );
+
+        graph.add_pass(
+            "Post Process",
+            |pass| {
+                pass.read(lit_scene);
+                pass.write(surface)
+            },
+            |ctx| {
+                self.post_chain.run(
+                    &self.render_device.device,
+                    &self.render_device.queue,
+                    ctx.encoder,
+                    ctx.texture(scene_color),
+                    ctx.texture(surface),
+                );
+            },
+        );
+
+        graph
+            .execute(&self.render_device.device, &mut encoder, &mut self.transient_pool)
+            .unwrap();
 
         self.render_device.queue.submit(std::iter::once(encoder.finish()));
         output.present();
     }
 }
 
+// Pipelines qui dessinent avec le depth buffer de la caméra
+struct ScenePipelines {
+    depth_mode: DepthMode,
+    render: wgpu::RenderPipeline,
+    skinned: wgpu::RenderPipeline,
+    light: wgpu::RenderPipeline,
+}
+
+impl ScenePipelines {
+    fn new(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        skinned_layout: &wgpu::PipelineLayout,
+        depth_mode: DepthMode,
+    ) -> Self {
+        let render = create_render_pipeline(
+            device,
+            layout,
+            HDR_FORMAT,
+            depth_mode,
+            &[VertexData::desc(), InstanceData::desc()],
+            include_spirv!(concat!(env!("OUT_DIR"), "/basic.spv")),
+        );
+
+        // Même fragment shader que le pipeline de base, seul le vertex shader change
+        let skinned = create_render_pipeline_with(
+            device,
+            skinned_layout,
+            HDR_FORMAT,
+            depth_mode,
+            &[VertexData::desc(), InstanceData::desc(), SkinVertex::desc()],
+            &device.create_shader_module(include_spirv!(concat!(env!("OUT_DIR"), "/skinned.spv"))),
+            &device.create_shader_module(include_spirv!(concat!(env!("OUT_DIR"), "/basic.spv"))),
+        );
+
+        let light = create_render_pipeline(
+            device,
+            layout,
+            HDR_FORMAT,
+            depth_mode,
+            &[VertexData::desc(), LightGizmoData::desc()],
+            include_spirv!(concat!(env!("OUT_DIR"), "/light.spv")),
+        );
+
+        Self {
+            depth_mode,
+            render,
+            skinned,
+            light,
+        }
+    }
+}
+
 fn create_render_pipeline(
     device: &wgpu::Device,
     layout: &wgpu::PipelineLayout,
     color_format: wgpu::TextureFormat,
+    depth_mode: DepthMode,
     vertex_layouts: &[wgpu::VertexBufferLayout],
     shader_desc: wgpu::ShaderModuleDescriptor,
 ) -> wgpu::RenderPipeline {
     let shader = device.create_shader_module(shader_desc);
 
+    create_render_pipeline_with(device, layout, color_format, depth_mode, vertex_layouts, &shader, &shader)
+}
+
+fn create_render_pipeline_with(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    color_format: wgpu::TextureFormat,
+    depth_mode: DepthMode,
+    vertex_layouts: &[wgpu::VertexBufferLayout],
+    vertex_shader: &wgpu::ShaderModule,
+    fragment_shader: &wgpu::ShaderModule,
+) -> wgpu::RenderPipeline {
     device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
         label: Some("Render Pipeline"),
         layout: Some(layout),
         vertex: wgpu::VertexState {
-            module: &shader,
+            module: vertex_shader,
             entry_point: "vs_main",
             buffers: vertex_layouts,
         },
         fragment: Some(wgpu::FragmentState {
-            module: &shader,
+            module: fragment_shader,
             entry_point: "fs_main",
             targets: &[Some(wgpu::ColorTargetState {
                 format: color_format,
@@ -366,7 +914,7 @@ fn create_render_pipeline(
         depth_stencil: Some(wgpu::DepthStencilState {
             format: wgpu::TextureFormat::Depth32Float,
             depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
+            depth_compare: depth_mode.compare_function(),
             stencil: wgpu::StencilState::default(),
             bias: wgpu::DepthBiasState::default(),
         }),
@@ -379,50 +927,184 @@ fn create_render_pipeline(
     })
 }
 
-#[derive(Copy, Clone)]
+// Composants propres à l'app
 struct Cube {
-    position: Vec3,
-    rotation: Quat,
-    velocity: Vec3,
     target_position: Vec3,
-    rotation_delta: Quat,
+    // Hauteur au-dessus de sa place d'où le cube est lâché
+    drop_height: f32,
+}
+
+// Cube sélectionné à la souris, grossi pour le distinguer
+struct Selected;
+
+struct OrbitLight {
+    animation: Animation,
+    playback: Playback,
 }
 
-fn compute_target_positions(cubes: &mut Vec<Cube>) {
-    let angular_step = TAU / cubes.len() as f32;
-    let circle_radius = 4.5;
+// Dessiné à part avec le pipeline skinné
+struct Tentacle {
+    material: MaterialId,
+}
+
+// Instances consécutives d'un même mesh dans le buffer d'instances
+struct MeshDraw {
+    mesh: Handle<GpuMesh>,
+    // Toutes pour les ombres, seulement celles dans le champ de la caméra pour la scène
+    instances: Range<u32>,
+    visible: Range<u32>,
+    floor: bool,
+}
 
-    for (i, cube) in cubes.iter_mut().enumerate() {
-        let angle = i as f32 * angular_step;
+// Entités du fichier de scène dont l'app a besoin
+struct SceneEntities {
+    camera: Entity,
+    orbit_light: Entity,
+    floor_material: MaterialId,
+}
 
-        cube.target_position.x = angle.cos() * circle_radius;
-        cube.target_position.y = 0.0;
-        cube.target_position.z = angle.sin() * circle_radius;
+// Le fichier décrit la disposition de la scène, le comportement propre à
+// l'app est rattaché aux entités d'après leur nom. Un fichier incomplet est
+// refusé avant de toucher aux entités existantes.
+fn spawn_scene(
+    file: &SceneFile,
+    scene: &mut SceneInstance,
+    world: &mut World,
+    assets: &mut AssetServer,
+    materials: &mut MaterialLibrary,
+    device: &wgpu::Device,
+) -> Result<SceneEntities, String> {
+    for name in ["camera", "orbit_light"] {
+        if file.entity(name).is_none() {
+            return Err(format!("the scene has no \"{name}\" entity"));
+        }
+    }
+
+    if !file.materials.contains_key("floor") {
+        return Err("the scene has no \"floor\" material".to_owned());
+    }
+
+    scene.spawn(file, world, assets, materials, device);
+
+    // 1, 2 et 3 changent de contrôleur pendant l'exécution
+    let camera = scene.entity("camera").unwrap();
+    let camera_transform = world.get::<Transform>(camera).map_or(Transform::IDENTITY, |transform| *transform);
+    world.insert(camera, OrbitController::from_transform(&camera_transform, 10.2).with_auto_rotate(0.1));
+
+    let orbit_light = scene.entity("orbit_light").unwrap();
+    world.insert(orbit_light, OrbitLight {
+        animation: build_orbit_light_animation(),
+        playback: Playback::new(PlayMode::Loop),
+    });
+
+    let mut tentacles = Vec::new();
+    world.query::<&Tentacle>(|entity, _| tentacles.push(entity));
+
+    for tentacle in tentacles {
+        if let Some(stage) = scene.entity("stage") {
+            world.insert(tentacle, Parent(stage));
+        } else {
+            world.remove::<Parent>(tentacle);
+        }
+    }
+
+    // Les corps dynamiques sont les cubes, le fichier donne leur place sur le
+    // sol. Ils tombent l'un après l'autre au-dessus et rebondissent.
+    let cubes: Vec<_> = scene
+        .entities()
+        .iter()
+        .copied()
+        .filter(|&entity| world.get::<RigidBody>(entity).is_some_and(|body| body.kind == BodyKind::Dynamic))
+        .collect();
+
+    for (i, entity) in cubes.into_iter().enumerate() {
+        let target_position = world.get::<Transform>(entity).map_or(Vec3::ZERO, |transform| transform.translation);
+
+        world.insert(entity, Cube {
+            target_position,
+            drop_height: 2.0 + i as f32 * 0.6,
+        });
     }
+
+    drop_cubes(world);
+
+    Ok(SceneEntities {
+        camera,
+        orbit_light,
+        floor_material: scene.material("floor").unwrap(),
+    })
+}
+
+// Les cubes sont déplacés par la physique, il ne reste qu'à grossir le cube sélectionné
+fn update_cubes(world: &mut World, _dt: f32) {
+    world.query::<(&mut Transform, &Cube, Option<&Selected>)>(|_, (transform, _, selected)| {
+        transform.scale = Vec3::splat(if selected.is_some() { 1.3 } else { 1.0 });
+    });
+}
+
+// Replace les cubes au-dessus de leur place avec une rotation au hasard
+fn drop_cubes(world: &mut World) {
+    let mut rng = rand::thread_rng();
+
+    world.query::<(&mut Transform, &mut RigidBody, &Cube)>(|_, (transform, body, cube)| {
+        transform.translation = cube.target_position + Vec3::Y * cube.drop_height;
+        transform.rotation =
+            Quat::from_rotation_x(rng.gen_range(-0.5..0.5)) * Quat::from_rotation_z(rng.gen_range(-0.5..0.5));
+        body.linear_velocity = Vec3::ZERO;
+        body.angular_velocity = vec3(rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0), rng.gen_range(-2.0..2.0));
+    });
+}
+
+fn update_orbit_light(world: &mut World, dt: f32) {
+    world.query::<(&mut Transform, &mut Light, &mut OrbitLight)>(|_, (transform, light, orbit)| {
+        orbit.playback.advance(&orbit.animation, dt);
+        let time = orbit.playback.time(orbit.animation.duration());
+
+        orbit.animation.sample(time, transform);
+
+        if let Some(color) = orbit.animation.sample_color(time) {
+            light.color = color;
+        }
+    });
 }
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-struct VertexData {
-    position: [f32; 3],
-    normal: [f32; 3],
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct InstanceData {
+    model_matrix: [f32; 16],
+    material_index: u32,
 }
 
-impl VertexData {
+impl InstanceData {
     fn desc() -> wgpu::VertexBufferLayout<'static> {
         wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<VertexData>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
+            array_stride: std::mem::size_of::<InstanceData>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
             attributes: &[
                 wgpu::VertexAttribute {
                     offset: 0,
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float32x3,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32x4,
                 },
                 wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x3,
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    shader_location: 8,
+                    format: wgpu::VertexFormat::Uint32,
                 },
             ],
         }
@@ -431,34 +1113,40 @@ impl VertexData {
 
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-struct InstanceData {
+struct LightGizmoData {
     model_matrix: [f32; 16],
+    color: [f32; 4],
 }
 
-impl InstanceData {
+impl LightGizmoData {
     fn desc() -> wgpu::VertexBufferLayout<'static> {
         wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<InstanceData>() as wgpu::BufferAddress,
+            array_stride: std::mem::size_of::<LightGizmoData>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Instance,
             attributes: &[
                 wgpu::VertexAttribute {
                     offset: 0,
-                    shader_location: 2,
+                    shader_location: 4,
                     format: wgpu::VertexFormat::Float32x4,
                 },
                 wgpu::VertexAttribute {
                     offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
-                    shader_location: 3,
+                    shader_location: 5,
                     format: wgpu::VertexFormat::Float32x4,
                 },
                 wgpu::VertexAttribute {
                     offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
-                    shader_location: 4,
+                    shader_location: 6,
                     format: wgpu::VertexFormat::Float32x4,
                 },
                 wgpu::VertexAttribute {
                     offset: std::mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
-                    shader_location: 5,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    shader_location: 8,
                     format: wgpu::VertexFormat::Float32x4,
                 },
             ],
@@ -466,103 +1154,144 @@ impl InstanceData {
     }
 }
 
-struct Mesh {
-    vertices: Vec<VertexData>,
-    indices: Vec<u32>,
+// Normal map qui biseaute les bords de chaque face
+fn build_bevel_normal_texture(device: &wgpu::Device, queue: &wgpu::Queue) -> Texture {
+    let size = 128;
+    let bevel = size / 10;
+
+    let slope = |coord: u32| {
+        if coord < bevel {
+            -0.7
+        } else if coord >= size - bevel {
+            0.7
+        } else {
+            0.0
+        }
+    };
+
+    let pixels: Vec<u8> = (0..size * size)
+        .flat_map(|i| {
+            // Le y de l'espace tangent monte alors que les lignes de la texture descendent
+            let normal = vec3(slope(i % size), -slope(i / size), 1.0).normalize();
+            let encoded = (normal * 0.5 + 0.5) * 255.0;
+            [encoded.x as u8, encoded.y as u8, encoded.z as u8, 255]
+        })
+        .collect();
+
+    Texture::from_rgba8(device, queue, "Bevel Normal Texture", size, size, &pixels, ColorSpace::Linear)
 }
 
-fn build_cube_mesh() -> Mesh {
-    let s = 0.5;
-
-    Mesh {
-        #[rustfmt::skip]
-        vertices: vec![
-            // +x face
-            VertexData { position: [s, s, s], normal: [1.0, 0.0, 0.0] },
-            VertexData { position: [s, -s, s], normal: [1.0, 0.0, 0.0] },
-            VertexData { position: [s, -s, -s], normal: [1.0, 0.0, 0.0] },
-            VertexData { position: [s, s, -s], normal: [1.0, 0.0, 0.0] },
-
-            // -x face
-            VertexData { position: [-s, s, -s], normal: [-1.0, 0.0, 0.0] },
-            VertexData { position: [-s, -s, -s], normal: [-1.0, 0.0, 0.0] },
-            VertexData { position: [-s, -s, s], normal: [-1.0, 0.0, 0.0] },
-            VertexData { position: [-s, s, s], normal: [-1.0, 0.0, 0.0] },
-
-            // +y face
-            VertexData { position: [-s, s, -s], normal: [0.0, 1.0, 0.0] },
-            VertexData { position: [-s, s, s], normal: [0.0, 1.0, 0.0] },
-            VertexData { position: [s, s, s], normal: [0.0, 1.0, 0.0] },
-            VertexData { position: [s, s, -s], normal: [0.0, 1.0, 0.0] },
-
-            // -y face
-            VertexData { position: [-s, -s, s], normal: [0.0, -1.0, 0.0] },
-            VertexData { position: [-s, -s, -s], normal: [0.0, -1.0, 0.0] },
-            VertexData { position: [s, -s, -s], normal: [0.0, -1.0, 0.0] },
-            VertexData { position: [s, -s, s], normal: [0.0, -1.0, 0.0] },
-
-            // +z face
-            VertexData { position: [-s, s, s], normal: [0.0, 0.0, 1.0] },
-            VertexData { position: [-s, -s, s], normal: [0.0, 0.0, 1.0] },
-            VertexData { position: [s, -s, s], normal: [0.0, 0.0, 1.0] },
-            VertexData { position: [s, s, s], normal: [0.0, 0.0, 1.0] },
-
-            // -z face
-            VertexData { position: [-s, -s, -s], normal: [0.0, 0.0, -1.0] },
-            VertexData { position: [-s, s, -s], normal: [0.0, 0.0, -1.0] },
-            VertexData { position: [s, s, -s], normal: [0.0, 0.0, -1.0] },
-            VertexData { position: [s, -s, -s], normal: [0.0, 0.0, -1.0] },
-        ],
-
-        #[rustfmt::skip]
-        indices: vec![
-            // +x triangles
-            0, 1, 3,  1, 2, 3,
-
-            // -x triangles
-            4, 5, 7,  5, 6, 7,
-
-            // +y triangles
-            8, 9, 11,  9, 10, 11,
-
-            // -y triangles
-            12, 13, 15,  13, 14, 15,
-
-            // +z triangles
-            16, 17, 19,  17, 18, 19,
-
-            // -z triangles
-            20, 21, 23,  21, 22, 23,
-        ],
+// Une boucle autour de la scène qui s'enroule autour d'un cercle, échantillonnée
+// en clés cubiques, avec une couleur qui passe du blanc chaud au bleu
+fn build_orbit_light_animation() -> Animation {
+    let key_count = 64;
+    let duration = TAU;
+    let inner_radius = 1.2;
+    let outer_radius = 4.5;
+
+    let mut translation = Track::new(Interpolation::Cubic);
+    for key in 0..=key_count {
+        let time = key as f32 / key_count as f32 * duration;
+        let (si, ci) = f32::sin_cos(time * 5.0);
+        let (so, co) = f32::sin_cos(time);
+
+        translation = translation.key(
+            time,
+            vec3(
+                co * (ci * inner_radius + outer_radius),
+                si * inner_radius,
+                so * (ci * inner_radius + outer_radius),
+            ),
+        );
     }
+
+    let warm = vec3(1.0, 0.9, 0.8);
+    let cold = vec3(0.7, 0.8, 1.0);
+    let color = Track::new(Interpolation::Linear)
+        .key_eased(0.0, warm, Easing::SineInOut)
+        .key_eased(duration * 0.5, cold, Easing::SineInOut)
+        .key(duration, warm);
+
+    Animation::default().with_translation(translation).with_color(color)
 }
 
-struct GpuMesh {
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
-    index_count: u32,
+const TENTACLE_JOINTS: usize = 4;
+const TENTACLE_SEGMENT_LENGTH: f32 = 0.8;
+
+// Pile de cylindres de plus en plus fins, chaque sommet est partagé entre les
+// deux articulations les plus proches
+fn build_tentacle() -> (Mesh, Vec<SkinVertex>) {
+    let mut mesh = Mesh::default();
+    let piece_count = 12;
+    let piece_height = TENTACLE_SEGMENT_LENGTH * TENTACLE_JOINTS as f32 / piece_count as f32;
+
+    for i in 0..piece_count {
+        let radius = 0.3 * (1.0 - i as f32 / piece_count as f32 * 0.7);
+        let mut piece = primitives::cylinder(radius, piece_height, 16);
+        piece.transform(Mat4::from_translation(Vec3::Y * piece_height * (i as f32 + 0.5)));
+        mesh.append(&piece);
+    }
+
+    let skin = mesh
+        .vertices
+        .iter()
+        .map(|vertex| {
+            let position = (vertex.position[1] / TENTACLE_SEGMENT_LENGTH).clamp(0.0, (TENTACLE_JOINTS - 1) as f32);
+            let joint = (position as usize).min(TENTACLE_JOINTS - 2);
+            let weight = position - joint as f32;
+
+            SkinVertex {
+                joints: [joint as u16, joint as u16 + 1, 0, 0],
+                weights: [1.0 - weight, weight, 0.0, 0.0],
+            }
+        })
+        .collect();
+
+    (mesh, skin)
 }
 
-impl GpuMesh {
-    fn new(mesh: &Mesh, device: &wgpu::Device) -> Self {
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("VertexData Buffer"),
-            contents: bytemuck::cast_slice(&mesh.vertices),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
+// Une chaîne verticale, chaque articulation au bout de la précédente
+fn build_tentacle_skeleton() -> Skeleton {
+    let joints = (0..TENTACLE_JOINTS)
+        .map(|i| Joint {
+            name: Some(format!("tentacle_{i}")),
+            parent: i.checked_sub(1),
+            rest: Transform::from_translation(if i == 0 { Vec3::ZERO } else { Vec3::Y * TENTACLE_SEGMENT_LENGTH }),
+            inverse_bind_matrix: Mat4::from_translation(Vec3::NEG_Y * TENTACLE_SEGMENT_LENGTH * i as f32),
+        })
+        .collect();
+
+    Skeleton::new(joints)
+}
 
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Index Buffer"),
-            contents: bytemuck::cast_slice(&mesh.indices),
-            usage: wgpu::BufferUsages::INDEX,
-        });
+// Un balancement de gauche à droite et un enroulement vers l'avant
+fn build_tentacle_clips() -> Vec<AnimationClip> {
+    let key_count = 9;
+    let duration = 3.0;
+    let times: Vec<f32> = (0..key_count).map(|key| key as f32 / (key_count - 1) as f32 * duration).collect();
+
+    let clip = |name: &str, axis: Vec3, amplitude: f32| {
+        let channels = (1..TENTACLE_JOINTS)
+            .map(|joint| Channel {
+                joint,
+                interpolation: Interpolation::Linear,
+                times: times.clone(),
+                values: ChannelValues::Rotation(
+                    times
+                        .iter()
+                        .map(|time| {
+                            let phase = time / duration * TAU - joint as f32 * 0.6;
+                            Quat::from_axis_angle(axis, phase.sin() * amplitude)
+                        })
+                        .collect(),
+                ),
+            })
+            .collect();
 
-        Self {
-            vertex_buffer,
-            index_buffer,
-            index_count: mesh.indices.len() as u32,
-        }
-    }
+        AnimationClip::new(Some(name.to_owned()), channels)
+    };
+
+    vec![clip("sway", Vec3::Z, 0.5), clip("curl", Vec3::X, 0.35)]
 }
 
 struct RenderDevice {
@@ -636,36 +1365,3 @@ impl RenderDevice {
     }
 }
 
-fn create_depth_texture(device: &wgpu::Device, width: u32, height: u32) -> wgpu::Texture {
-    device.create_texture(&wgpu::TextureDescriptor {
-        label: Some("Depth Texture"),
-        size: wgpu::Extent3d {
-            width,
-            height,
-            depth_or_array_layers: 1,
-        },
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Depth32Float,
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-        view_formats: &[],
-    })
-}
-
-#[repr(C)]
-#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-struct CameraUniform {
-    view_proj_matrix: [f32; 16],
-    pos: [f32; 3],
-    _pad: u32,
-}
-
-#[repr(C)]
-#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
-struct LightUniform {
-    pos: [f32; 3],
-    _pad0: u32,
-    color: [f32; 3],
-    _pad1: u32,
-}
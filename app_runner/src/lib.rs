@@ -12,7 +12,7 @@ use winit::{
 #[cfg(target_arch = "wasm32")]
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
 pub fn wasm_main() {
-    use app::{lux_app_drop, lux_app_new, lux_app_on_resize, lux_app_update};
+    use app::{lux_app_drop, lux_app_new, lux_app_on_resize, lux_app_on_window_event, lux_app_update};
     std::panic::set_hook(Box::new(console_error_panic_hook::hook));
 
     const WIDTH: u32 = 1280;
@@ -46,24 +46,30 @@ pub fn wasm_main() {
         control_flow.set_poll();
 
         match event {
-            Event::WindowEvent { event, .. } => match event {
-                WindowEvent::CloseRequested => {
-                    *control_flow = ControlFlow::Exit;
-                    unsafe {
-                        lux_app_drop(app);
-                    }
+            Event::WindowEvent { event, .. } => {
+                unsafe {
+                    lux_app_on_window_event(app, &event);
                 }
 
-                WindowEvent::Resized(physical_size) => unsafe {
-                    lux_app_on_resize(app, physical_size.width, physical_size.height);
-                },
+                match event {
+                    WindowEvent::CloseRequested => {
+                        *control_flow = ControlFlow::Exit;
+                        unsafe {
+                            lux_app_drop(app);
+                        }
+                    }
 
-                WindowEvent::ScaleFactorChanged { new_inner_size, .. } => unsafe {
-                    lux_app_on_resize(app, new_inner_size.width, new_inner_size.height);
-                },
+                    WindowEvent::Resized(physical_size) => unsafe {
+                        lux_app_on_resize(app, physical_size.width, physical_size.height);
+                    },
 
-                _ => {}
-            },
+                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => unsafe {
+                        lux_app_on_resize(app, new_inner_size.width, new_inner_size.height);
+                    },
+
+                    _ => {}
+                }
+            }
 
             Event::MainEventsCleared => unsafe {
                 lux_app_update(app);
@@ -25,6 +25,7 @@ struct AppCode {
     app_drop: unsafe fn(*mut c_void),
     app_update: unsafe fn(*mut c_void),
     app_on_resize: unsafe fn(*mut c_void, u32, u32),
+    app_on_window_event: unsafe fn(*mut c_void, &WindowEvent),
 }
 
 impl AppCode {
@@ -38,6 +39,7 @@ impl AppCode {
             app_drop: std::mem::transmute(GetProcAddress(dll, b"lux_app_drop\0".as_ptr().cast())),
             app_update: std::mem::transmute(GetProcAddress(dll, b"lux_app_update\0".as_ptr().cast())),
             app_on_resize: std::mem::transmute(GetProcAddress(dll, b"lux_app_on_resize\0".as_ptr().cast())),
+            app_on_window_event: std::mem::transmute(GetProcAddress(dll, b"lux_app_on_window_event\0".as_ptr().cast())),
         }
     }
 }
@@ -67,19 +69,25 @@ fn main() {
         control_flow.set_poll();
 
         match event {
-            Event::WindowEvent { event, .. } => match event {
-                WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+            Event::WindowEvent { event, .. } => {
+                unsafe {
+                    (app_code.app_on_window_event)(app, &event);
+                }
 
-                WindowEvent::Resized(physical_size) => unsafe {
-                    (app_code.app_on_resize)(app, physical_size.width, physical_size.height);
-                },
+                match event {
+                    WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
 
-                WindowEvent::ScaleFactorChanged { new_inner_size, .. } => unsafe {
-                    (app_code.app_on_resize)(app, new_inner_size.width, new_inner_size.height);
-                },
+                    WindowEvent::Resized(physical_size) => unsafe {
+                        (app_code.app_on_resize)(app, physical_size.width, physical_size.height);
+                    },
 
-                _ => {}
-            },
+                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => unsafe {
+                        (app_code.app_on_resize)(app, new_inner_size.width, new_inner_size.height);
+                    },
+
+                    _ => {}
+                }
+            }
 
             Event::MainEventsCleared => unsafe {
                 let wait_status = WaitForSingleObjectEx(dll_watch_handle, 0, 0);
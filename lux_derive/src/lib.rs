@@ -31,6 +31,11 @@ fn impl_macro(ast: &syn::DeriveInput) -> TokenStream {
         pub unsafe fn lux_app_on_resize(app: *mut std::ffi::c_void, width: u32, height: u32) {
             (app as *mut #app_name).as_mut().unwrap().on_resize(width, height);
         }
+
+        #[no_mangle]
+        pub unsafe fn lux_app_on_window_event(app: *mut std::ffi::c_void, event: &winit::event::WindowEvent) {
+            (app as *mut #app_name).as_mut().unwrap().on_window_event(event);
+        }
     };
 
     app_functions.into()
@@ -0,0 +1,60 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use lux::mesh::{binary, Mesh};
+
+// Convertit un .obj, .gltf ou .glb en .lmesh, tous les meshes du fichier
+// partagent les mêmes buffers avec un sous-mesh par primitive
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let mut optimize = true;
+    let mut input = None;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--no-optimize" => optimize = false,
+            "-o" => output = args.next().map(PathBuf::from),
+            _ if input.is_none() => input = Some(PathBuf::from(arg)),
+            _ => return usage(),
+        }
+    }
+
+    let Some(input) = input else {
+        return usage();
+    };
+
+    let output = output.unwrap_or_else(|| input.with_extension("lmesh"));
+
+    let (mut mesh, sub_meshes) = match Mesh::load_sub_meshes(&input) {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            eprintln!("{}: {error}", input.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if optimize {
+        mesh.optimize_sub_meshes_vertex_cache(&sub_meshes);
+    }
+
+    if let Err(error) = binary::save(&mesh, &sub_meshes, &output) {
+        eprintln!("{}: {error}", output.display());
+        return ExitCode::FAILURE;
+    }
+
+    println!(
+        "{} -> {} ({} vertices, {} triangles, {} sub-meshes)",
+        input.display(),
+        output.display(),
+        mesh.vertices.len(),
+        mesh.indices.len() / 3,
+        sub_meshes.len()
+    );
+
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: mesh_converter <input.obj|gltf|glb> [-o output.lmesh] [--no-optimize]");
+    ExitCode::FAILURE
+}
@@ -0,0 +1,485 @@
+use std::{
+    f32::consts::{FRAC_PI_2, PI, TAU},
+    ops::{Add, Mul},
+};
+
+use glam::{Quat, Vec3, Vec4};
+
+use crate::transform::Transform;
+
+// Valeur animable : elle doit pouvoir être interpolée et combinée
+// linéairement pour les splines
+pub trait Keyframe: Copy + Add<Output = Self> + Mul<f32, Output = Self> {
+    fn interpolate(self, other: Self, t: f32) -> Self;
+
+    // Appelé après une interpolation cubique, ex: renormaliser un quaternion
+    fn finish(self) -> Self {
+        self
+    }
+}
+
+impl Keyframe for f32 {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Keyframe for Vec3 {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Keyframe for Vec4 {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Keyframe for Quat {
+    fn interpolate(self, other: Self, t: f32) -> Self {
+        self.slerp(other, t)
+    }
+
+    fn finish(self) -> Self {
+        self.normalize()
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Interpolation {
+    // La valeur saute d'une clé à l'autre
+    Step,
+    #[default]
+    Linear,
+    // Spline d'Hermite, les tangentes viennent des clés voisines (Catmull-Rom)
+    // pour une `Track` ou sont données explicitement par glTF
+    Cubic,
+}
+
+// Courbes de Robert Penner, `apply` envoie [0, 1] sur une courbe qui part de 0
+// et arrive à 1
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    // Dépasse légèrement la cible avant de revenir
+    BackOut,
+    ElasticOut,
+    BounceOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Self::Linear => t,
+            Self::QuadIn => t * t,
+            Self::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Self::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Self::CubicIn => t * t * t,
+            Self::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Self::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Self::SineIn => 1.0 - (t * FRAC_PI_2).cos(),
+            Self::SineOut => (t * FRAC_PI_2).sin(),
+            Self::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+            Self::BackOut => {
+                let c1 = 1.70158;
+                let c3 = c1 + 1.0;
+                1.0 + c3 * (t - 1.0).powi(3) + c1 * (t - 1.0).powi(2)
+            }
+            Self::ElasticOut => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * TAU / 3.0).sin() + 1.0
+                }
+            }
+            Self::BounceOut => {
+                let n1 = 7.5625;
+                let d1 = 2.75;
+
+                if t < 1.0 / d1 {
+                    n1 * t * t
+                } else if t < 2.0 / d1 {
+                    let t = t - 1.5 / d1;
+                    n1 * t * t + 0.75
+                } else if t < 2.5 / d1 {
+                    let t = t - 2.25 / d1;
+                    n1 * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / d1;
+                    n1 * t * t + 0.984375
+                }
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Key<T> {
+    pub time: f32,
+    pub value: T,
+    // Courbe appliquée entre cette clé et la suivante
+    pub easing: Easing,
+}
+
+// Clés triées par temps. Avant la première et après la dernière, la valeur
+// est maintenue.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track<T> {
+    pub interpolation: Interpolation,
+    pub keys: Vec<Key<T>>,
+}
+
+impl<T: Keyframe> Track<T> {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            interpolation,
+            keys: Vec::new(),
+        }
+    }
+
+    pub fn key(self, time: f32, value: T) -> Self {
+        self.key_eased(time, value, Easing::Linear)
+    }
+
+    pub fn key_eased(mut self, time: f32, value: T, easing: Easing) -> Self {
+        let index = self.keys.partition_point(|key| key.time <= time);
+        self.keys.insert(index, Key { time, value, easing });
+        self
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keys.last().map_or(0.0, |key| key.time)
+    }
+
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let (previous, next, t) = find_segment(self.keys.len(), |key| self.keys[key].time, time)?;
+        let t = self.keys[previous].easing.apply(t);
+        let value = |key: usize| self.keys[key].value;
+
+        Some(match self.interpolation {
+            _ if previous == next => value(previous),
+            Interpolation::Step => value(previous),
+            Interpolation::Linear => value(previous).interpolate(value(next), t),
+            Interpolation::Cubic => {
+                let dt = self.keys[next].time - self.keys[previous].time;
+                let out_tangent = self.catmull_rom_tangent(previous) * dt;
+                let in_tangent = self.catmull_rom_tangent(next) * dt;
+
+                hermite(value(previous), out_tangent, value(next), in_tangent, t)
+            }
+        })
+    }
+
+    fn catmull_rom_tangent(&self, key: usize) -> T {
+        let before = key.saturating_sub(1);
+        let after = (key + 1).min(self.keys.len() - 1);
+        let dt = self.keys[after].time - self.keys[before].time;
+
+        if dt > 0.0 {
+            (self.keys[after].value + self.keys[before].value * -1.0) * (1.0 / dt)
+        } else {
+            self.keys[key].value * 0.0
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationEvent {
+    pub time: f32,
+    pub name: String,
+}
+
+// Pistes typées d'un même objet, celles qui valent None ne sont pas animées
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Animation {
+    pub translation: Option<Track<Vec3>>,
+    pub rotation: Option<Track<Quat>>,
+    pub scale: Option<Track<Vec3>>,
+    // Couleur linéaire, pour les lumières ou les matériaux
+    pub color: Option<Track<Vec3>>,
+    pub events: Vec<AnimationEvent>,
+}
+
+impl Animation {
+    pub fn with_translation(mut self, track: Track<Vec3>) -> Self {
+        self.translation = Some(track);
+        self
+    }
+
+    pub fn with_rotation(mut self, track: Track<Quat>) -> Self {
+        self.rotation = Some(track);
+        self
+    }
+
+    pub fn with_scale(mut self, track: Track<Vec3>) -> Self {
+        self.scale = Some(track);
+        self
+    }
+
+    pub fn with_color(mut self, track: Track<Vec3>) -> Self {
+        self.color = Some(track);
+        self
+    }
+
+    pub fn with_event(mut self, time: f32, name: impl Into<String>) -> Self {
+        self.events.push(AnimationEvent {
+            time,
+            name: name.into(),
+        });
+        self
+    }
+
+    // La plus longue des pistes et des événements
+    pub fn duration(&self) -> f32 {
+        let tracks = [
+            self.translation.as_ref().map(Track::duration),
+            self.rotation.as_ref().map(Track::duration),
+            self.scale.as_ref().map(Track::duration),
+            self.color.as_ref().map(Track::duration),
+        ];
+
+        tracks
+            .into_iter()
+            .flatten()
+            .chain(self.events.iter().map(|event| event.time))
+            .fold(0.0, f32::max)
+    }
+
+    // Écrase les composantes animées de `transform`
+    pub fn sample(&self, time: f32, transform: &mut Transform) {
+        if let Some(translation) = self.translation.as_ref().and_then(|track| track.sample(time)) {
+            transform.translation = translation;
+        }
+
+        if let Some(rotation) = self.rotation.as_ref().and_then(|track| track.sample(time)) {
+            transform.rotation = rotation;
+        }
+
+        if let Some(scale) = self.scale.as_ref().and_then(|track| track.sample(time)) {
+            transform.scale = scale;
+        }
+    }
+
+    pub fn sample_color(&self, time: f32) -> Option<Vec3> {
+        self.color.as_ref()?.sample(time)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PlayMode {
+    // S'arrête sur la dernière clé
+    #[default]
+    Once,
+    Loop,
+    // Aller puis retour, en boucle
+    PingPong,
+}
+
+// Position de lecture d'une animation, séparée de celle-ci pour qu'une même
+// animation puisse être jouée par plusieurs objets
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Playback {
+    pub mode: PlayMode,
+    pub speed: f32,
+    pub paused: bool,
+    // Temps écoulé depuis le début, sans bouclage
+    elapsed: f32,
+}
+
+impl Playback {
+    pub fn new(mode: PlayMode) -> Self {
+        Self {
+            mode,
+            speed: 1.0,
+            paused: false,
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    // Temps à passer à `Animation::sample`
+    pub fn time(&self, duration: f32) -> f32 {
+        if duration <= 0.0 {
+            return 0.0;
+        }
+
+        match self.mode {
+            PlayMode::Once => self.elapsed.min(duration),
+            PlayMode::Loop => self.elapsed.rem_euclid(duration),
+            PlayMode::PingPong => {
+                let time = self.elapsed.rem_euclid(2.0 * duration);
+                if time > duration {
+                    2.0 * duration - time
+                } else {
+                    time
+                }
+            }
+        }
+    }
+
+    pub fn is_finished(&self, duration: f32) -> bool {
+        self.mode == PlayMode::Once && self.elapsed >= duration
+    }
+
+    // Avance de `dt` secondes et renvoie les événements franchis, dans
+    // l'ordre. Un événement peut revenir plusieurs fois si `dt` couvre
+    // plusieurs boucles.
+    pub fn advance<'a>(&mut self, animation: &'a Animation, dt: f32) -> Vec<&'a AnimationEvent> {
+        if self.paused {
+            return Vec::new();
+        }
+
+        let start = self.elapsed;
+        self.elapsed += (dt * self.speed).max(0.0);
+
+        let duration = animation.duration();
+        let mut fired: Vec<(f32, &AnimationEvent)> = Vec::new();
+
+        for event in &animation.events {
+            let mut add_occurrences = |offset: f32, period: Option<f32>| match period {
+                Some(period) => {
+                    // Jamais avant le premier passage, sinon un événement à la fin du
+                    // clip partirait aussi au temps 0
+                    let cycle = ((start - offset) / period).ceil().max(0.0);
+                    let mut occurrence = offset + cycle * period;
+                    while occurrence < self.elapsed {
+                        fired.push((occurrence, event));
+                        occurrence += period;
+                    }
+                }
+                None => {
+                    if (start..self.elapsed).contains(&offset) {
+                        fired.push((offset, event));
+                    }
+                }
+            };
+
+            match self.mode {
+                _ if duration <= 0.0 => add_occurrences(event.time, None),
+                PlayMode::Once => add_occurrences(event.time, None),
+                PlayMode::Loop => add_occurrences(event.time, Some(duration)),
+                PlayMode::PingPong => {
+                    add_occurrences(event.time, Some(2.0 * duration));
+
+                    // Au retour, sauf pour les événements aux extrémités qui
+                    // seraient sinon comptés deux fois
+                    if event.time > 0.0 && event.time < duration {
+                        add_occurrences(2.0 * duration - event.time, Some(2.0 * duration));
+                    }
+                }
+            }
+        }
+
+        fired.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        fired.into_iter().map(|(_, event)| event).collect()
+    }
+}
+
+// Interpolation simple entre deux valeurs, pour les transitions ponctuelles
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tween<T> {
+    pub from: T,
+    pub to: T,
+    pub duration: f32,
+    pub easing: Easing,
+    elapsed: f32,
+}
+
+impl<T: Keyframe> Tween<T> {
+    pub fn new(from: T, to: T, duration: f32, easing: Easing) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            easing,
+            elapsed: 0.0,
+        }
+    }
+
+    // Avance de `dt` secondes et renvoie la nouvelle valeur
+    pub fn update(&mut self, dt: f32) -> T {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+        self.value()
+    }
+
+    pub fn value(&self) -> T {
+        let t = if self.duration > 0.0 { self.elapsed / self.duration } else { 1.0 };
+        self.from.interpolate(self.to, self.easing.apply(t))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+// Renvoie les deux clés qui encadrent `time` et la position entre elles,
+// `previous == next` en dehors des clés
+pub(crate) fn find_segment(key_count: usize, key_time: impl Fn(usize) -> f32, time: f32) -> Option<(usize, usize, f32)> {
+    let last = key_count.checked_sub(1)?;
+
+    // Recherche dichotomique de la première clé après `time`
+    let (mut low, mut high) = (0, key_count);
+    while low < high {
+        let middle = (low + high) / 2;
+        if key_time(middle) <= time {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+
+    if low == 0 {
+        return Some((0, 0, 0.0));
+    }
+
+    if low > last {
+        return Some((last, last, 0.0));
+    }
+
+    let previous = low - 1;
+    let dt = key_time(low) - key_time(previous);
+    let t = if dt > 0.0 { (time - key_time(previous)) / dt } else { 0.0 };
+
+    Some((previous, low, t))
+}
+
+// `out_tangent` et `in_tangent` sont déjà multipliées par la durée du segment
+pub(crate) fn hermite<T: Keyframe>(start: T, out_tangent: T, end: T, in_tangent: T, t: f32) -> T {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    let value = start * (2.0 * t3 - 3.0 * t2 + 1.0)
+        + out_tangent * (t3 - 2.0 * t2 + t)
+        + end * (-2.0 * t3 + 3.0 * t2)
+        + in_tangent * (t3 - t2);
+
+    value.finish()
+}
@@ -0,0 +1,454 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Weak},
+    time::Duration,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Instant, SystemTime},
+};
+
+mod loaders;
+
+pub type AssetError = Box<dyn std::error::Error + Send + Sync>;
+
+pub trait Asset: Sized + 'static {
+    // Options de chargement, ex: l'espace colorimétrique d'une texture
+    type Settings: Clone + Default + Send + 'static;
+    // Résultat de la partie CPU du chargement, produit sur un thread de fond
+    type Loaded: Send + 'static;
+
+    fn load(path: &Path, settings: &Self::Settings) -> Result<Self::Loaded, AssetError>;
+
+    // Appelé sur le thread principal, c'est ici que se font les envois au GPU
+    fn create(loaded: Self::Loaded, device: &wgpu::Device, queue: &wgpu::Queue) -> Self;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoadState {
+    Loading,
+    Loaded,
+    // L'asset garde sa version précédente si un rechargement échoue
+    Failed(String),
+}
+
+struct HandleRef {
+    id: u64,
+}
+
+// Tant qu'il reste un handle vers un asset, il reste chargé
+pub struct Handle<T> {
+    inner: Arc<HandleRef>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    fn new(inner: Arc<HandleRef>) -> Self {
+        Self {
+            inner,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.inner.clone())
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.id == other.inner.id
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner.id.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Handle<{}>({})", std::any::type_name::<T>(), self.inner.id)
+    }
+}
+
+struct Entry<T: Asset> {
+    path: Option<PathBuf>,
+    settings: T::Settings,
+    handle: Weak<HandleRef>,
+    asset: Option<T>,
+    state: LoadState,
+    version: u32,
+    #[cfg(not(target_arch = "wasm32"))]
+    modified: Option<SystemTime>,
+}
+
+struct Storage<T: Asset> {
+    entries: HashMap<u64, Entry<T>>,
+    paths: HashMap<PathBuf, u64>,
+}
+
+impl<T: Asset> Default for Storage<T> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            paths: HashMap::new(),
+        }
+    }
+}
+
+// Les opérations qui ne dépendent pas du type de l'asset
+trait AnyStorage {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    #[cfg(not(target_arch = "wasm32"))]
+    fn changed_files(&mut self) -> Vec<u64>;
+    fn reload(&mut self, id: u64, workers: &Workers);
+    fn remove_unused(&mut self);
+}
+
+impl<T: Asset> AnyStorage for Storage<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn changed_files(&mut self) -> Vec<u64> {
+        self.entries
+            .iter_mut()
+            .filter(|(_, entry)| entry.state != LoadState::Loading)
+            .filter_map(|(&id, entry)| {
+                let modified = modified_time(entry.path.as_ref()?);
+                (modified != entry.modified).then(|| {
+                    entry.modified = modified;
+                    id
+                })
+            })
+            .collect()
+    }
+
+    fn reload(&mut self, id: u64, workers: &Workers) {
+        let Some(entry) = self.entries.get_mut(&id) else {
+            return;
+        };
+
+        if let Some(path) = entry.path.clone() {
+            entry.state = LoadState::Loading;
+            workers.spawn::<T>(id, path, entry.settings.clone());
+        }
+    }
+
+    fn remove_unused(&mut self) {
+        self.entries.retain(|_, entry| entry.handle.strong_count() > 0);
+        self.paths.retain(|_, id| self.entries.contains_key(id));
+    }
+}
+
+type Completion = Box<dyn FnOnce(&mut AssetServer, &wgpu::Device, &wgpu::Queue) + Send>;
+type Job = Box<dyn FnOnce() -> Completion + Send>;
+
+// Threads de chargement. Ils sont joints à la destruction du serveur pour
+// qu'aucun ne tourne encore quand la DLL de l'app est déchargée. Le web n'a pas
+// de threads : le fichier y est lu dans `spawn` et seule la création attend
+// le prochain `update`.
+struct Workers {
+    #[cfg(not(target_arch = "wasm32"))]
+    jobs: Option<mpsc::Sender<Job>>,
+    #[cfg(target_arch = "wasm32")]
+    completion_sender: mpsc::Sender<Completion>,
+    completions: mpsc::Receiver<Completion>,
+    #[cfg(not(target_arch = "wasm32"))]
+    threads: Vec<thread::JoinHandle<()>>,
+}
+
+impl Workers {
+    #[cfg(target_arch = "wasm32")]
+    fn new(_count: usize) -> Self {
+        let (completion_sender, completions) = mpsc::channel();
+
+        Self {
+            completion_sender,
+            completions,
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn new(count: usize) -> Self {
+        let (job_sender, job_receiver) = mpsc::channel::<Job>();
+        let (completion_sender, completions) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let threads = (0..count)
+            .map(|_| {
+                let job_receiver = job_receiver.clone();
+                let completion_sender = completion_sender.clone();
+
+                thread::spawn(move || loop {
+                    let job = job_receiver.lock().unwrap().recv();
+
+                    match job {
+                        Ok(job) => {
+                            if completion_sender.send(job()).is_err() {
+                                return;
+                            }
+                        }
+                        Err(_) => return,
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            jobs: Some(job_sender),
+            completions,
+            threads,
+        }
+    }
+
+    fn spawn<T: Asset>(&self, id: u64, path: PathBuf, settings: T::Settings) {
+        let job: Job = Box::new(move || {
+            let result = T::load(&path, &settings).map_err(|error| format!("{}: {error}", path.display()));
+
+            Box::new(move |server: &mut AssetServer, device: &wgpu::Device, queue: &wgpu::Queue| {
+                server.finish_load::<T>(id, result, device, queue);
+            })
+        });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(jobs) = &self.jobs {
+            jobs.send(job).unwrap();
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        self.completion_sender.send(job()).unwrap();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for Workers {
+    fn drop(&mut self) {
+        self.jobs = None;
+
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+pub struct AssetServer {
+    // Intervalle entre deux vérifications des dates de modification, None
+    // désactive le rechargement automatique. Sans effet sur le web.
+    pub watch_interval: Option<Duration>,
+
+    root: PathBuf,
+    storages: HashMap<TypeId, Box<dyn AnyStorage>>,
+    next_id: u64,
+    #[cfg(not(target_arch = "wasm32"))]
+    last_watch: Instant,
+    workers: Workers,
+}
+
+impl AssetServer {
+    // Les chemins passés à `load` sont relatifs à `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            watch_interval: if cfg!(target_arch = "wasm32") {
+                None
+            } else {
+                Some(Duration::from_millis(500))
+            },
+            root: root.into(),
+            storages: HashMap::new(),
+            next_id: 0,
+            #[cfg(not(target_arch = "wasm32"))]
+            last_watch: Instant::now(),
+            workers: Workers::new(2),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn load<T: Asset>(&mut self, path: impl AsRef<Path>) -> Handle<T> {
+        self.load_with(path, T::Settings::default())
+    }
+
+    // Un même fichier n'est chargé qu'une fois par type d'asset, les réglages
+    // du premier chargement sont conservés
+    pub fn load_with<T: Asset>(&mut self, path: impl AsRef<Path>, settings: T::Settings) -> Handle<T> {
+        let path = self.root.join(path);
+
+        if let Some(handle) = self.existing_handle::<T>(&path) {
+            return handle;
+        }
+
+        let inner = Arc::new(HandleRef { id: self.next_id });
+        self.next_id += 1;
+
+        self.workers.spawn::<T>(inner.id, path.clone(), settings.clone());
+
+        let storage = self.storage_mut::<T>();
+        storage.paths.insert(path.clone(), inner.id);
+        storage.entries.insert(
+            inner.id,
+            Entry {
+                #[cfg(not(target_arch = "wasm32"))]
+                modified: modified_time(&path),
+                path: Some(path),
+                settings,
+                handle: Arc::downgrade(&inner),
+                asset: None,
+                state: LoadState::Loading,
+                version: 0,
+            },
+        );
+
+        Handle::new(inner)
+    }
+
+    // Asset créé par le code, il n'a pas de fichier et n'est jamais rechargé
+    pub fn add<T: Asset>(&mut self, asset: T) -> Handle<T> {
+        let inner = Arc::new(HandleRef { id: self.next_id });
+        self.next_id += 1;
+
+        self.storage_mut::<T>().entries.insert(
+            inner.id,
+            Entry {
+                path: None,
+                settings: T::Settings::default(),
+                handle: Arc::downgrade(&inner),
+                asset: Some(asset),
+                state: LoadState::Loaded,
+                version: 1,
+                #[cfg(not(target_arch = "wasm32"))]
+                modified: None,
+            },
+        );
+
+        Handle::new(inner)
+    }
+
+    pub fn get<T: Asset>(&self, handle: &Handle<T>) -> Option<&T> {
+        self.entry(handle)?.asset.as_ref()
+    }
+
+    pub fn get_mut<T: Asset>(&mut self, handle: &Handle<T>) -> Option<&mut T> {
+        let storage = self.storage_mut::<T>();
+        storage.entries.get_mut(&handle.inner.id)?.asset.as_mut()
+    }
+
+    pub fn state<T: Asset>(&self, handle: &Handle<T>) -> LoadState {
+        self.entry(handle).map_or(LoadState::Loading, |entry| entry.state.clone())
+    }
+
+    // Incrémenté à chaque (re)chargement réussi, 0 tant que l'asset n'est pas
+    // prêt. Permet de savoir quand recréer ce qui en dépend (pipelines...).
+    pub fn version<T: Asset>(&self, handle: &Handle<T>) -> u32 {
+        self.entry(handle).map_or(0, |entry| entry.version)
+    }
+
+    pub fn reload<T: Asset>(&mut self, handle: &Handle<T>) {
+        if let Some(storage) = self.storages.get_mut(&TypeId::of::<T>()) {
+            storage.reload(handle.inner.id, &self.workers);
+        }
+    }
+
+    // À appeler à chaque frame : finit les chargements terminés, relance ceux
+    // dont le fichier a changé et libère les assets qui n'ont plus de handle
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        while let Ok(completion) = self.workers.completions.try_recv() {
+            completion(self, device, queue);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(interval) = self.watch_interval {
+            if self.last_watch.elapsed() >= interval {
+                self.last_watch = Instant::now();
+
+                for storage in self.storages.values_mut() {
+                    for id in storage.changed_files() {
+                        storage.reload(id, &self.workers);
+                    }
+                }
+            }
+        }
+
+        for storage in self.storages.values_mut() {
+            storage.remove_unused();
+        }
+    }
+
+    fn existing_handle<T: Asset>(&mut self, path: &Path) -> Option<Handle<T>> {
+        let storage = self.storage_mut::<T>();
+        let id = storage.paths.get(path)?;
+        let inner = storage.entries.get(id)?.handle.upgrade()?;
+
+        Some(Handle::new(inner))
+    }
+
+    fn finish_load<T: Asset>(
+        &mut self,
+        id: u64,
+        result: Result<T::Loaded, String>,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        let Some(entry) = self.storage_mut::<T>().entries.get_mut(&id) else {
+            return;
+        };
+
+        match result {
+            Ok(loaded) => {
+                entry.asset = Some(T::create(loaded, device, queue));
+                entry.state = LoadState::Loaded;
+                entry.version += 1;
+            }
+            Err(error) => entry.state = LoadState::Failed(error),
+        }
+    }
+
+    fn entry<T: Asset>(&self, handle: &Handle<T>) -> Option<&Entry<T>> {
+        let storage = self.storages.get(&TypeId::of::<T>())?;
+        let storage = storage.as_any().downcast_ref::<Storage<T>>().unwrap();
+
+        storage.entries.get(&handle.inner.id)
+    }
+
+    fn storage_mut<T: Asset>(&mut self) -> &mut Storage<T> {
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::<Storage<T>>::default())
+            .as_any_mut()
+            .downcast_mut()
+            .unwrap()
+    }
+}
+
+pub(crate) fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,114 @@
+use std::{borrow::Cow, path::Path};
+
+use image::RgbaImage;
+
+use super::{extension, Asset, AssetError};
+use crate::{
+    ecs::scene::SceneFile,
+    material::Material,
+    mesh::{binary::MeshFile, GpuMesh, Mesh},
+    texture::{ColorSpace, Texture},
+};
+
+// Les .lmesh sont envoyés au GPU sans conversion, les autres formats passent
+// par `Mesh::load_sub_meshes` et gardent un sous-mesh par primitive
+impl Asset for GpuMesh {
+    type Settings = ();
+    type Loaded = MeshFile;
+
+    fn load(path: &Path, _settings: &()) -> Result<MeshFile, AssetError> {
+        if extension(path) == "lmesh" {
+            return Ok(MeshFile::load(path)?);
+        }
+
+        let (mesh, sub_meshes) = Mesh::load_sub_meshes(path)?;
+        Ok(MeshFile::from_mesh(&mesh, &sub_meshes))
+    }
+
+    fn create(file: MeshFile, device: &wgpu::Device, _queue: &wgpu::Queue) -> Self {
+        GpuMesh::from_view(&file.view(), device)
+    }
+}
+
+impl Asset for Texture {
+    type Settings = ColorSpace;
+    type Loaded = (String, RgbaImage, ColorSpace);
+
+    fn load(path: &Path, color_space: &ColorSpace) -> Result<Self::Loaded, AssetError> {
+        let image = image::open(path)?.to_rgba8();
+        Ok((path.to_string_lossy().into_owned(), image, *color_space))
+    }
+
+    fn create((label, image, color_space): Self::Loaded, device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        Texture::from_image(device, queue, &label, &image, color_space)
+    }
+}
+
+// Shaders déjà compilés en SPIR-V par nzslc
+impl Asset for wgpu::ShaderModule {
+    type Settings = ();
+    type Loaded = (String, Vec<u32>);
+
+    fn load(path: &Path, _settings: &()) -> Result<Self::Loaded, AssetError> {
+        const SPIRV_MAGIC_NUMBER: u32 = 0x0723_0203;
+
+        let bytes = std::fs::read(path)?;
+
+        // wgpu panique sur un SPIR-V invalide, autant le refuser ici
+        if bytes.is_empty() || bytes.len() % 4 != 0 {
+            return Err("invalid SPIR-V size".into());
+        }
+
+        let words: Vec<u32> = bytes
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .collect();
+
+        if words[0] != SPIRV_MAGIC_NUMBER {
+            return Err("missing SPIR-V magic number".into());
+        }
+
+        Ok((path.to_string_lossy().into_owned(), words))
+    }
+
+    fn create((label, words): Self::Loaded, device: &wgpu::Device, _queue: &wgpu::Queue) -> Self {
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(&label),
+            source: wgpu::ShaderSource::SpirV(Cow::Owned(words)),
+        })
+    }
+}
+
+// Premier matériau d'un fichier .mtl, il reste à l'ajouter à la `MaterialLibrary`
+impl Asset for Material {
+    type Settings = ();
+    type Loaded = Material;
+
+    fn load(path: &Path, _settings: &()) -> Result<Material, AssetError> {
+        let source = std::fs::read_to_string(path)?;
+        let materials = crate::mesh::obj::parse_mtl(&source)?;
+
+        materials
+            .first()
+            .map(|material| material.to_material())
+            .ok_or_else(|| "no material in file".into())
+    }
+
+    fn create(material: Material, _device: &wgpu::Device, _queue: &wgpu::Queue) -> Self {
+        material
+    }
+}
+
+// Gardé tel quel, c'est l'app qui recrée les entités quand la version change
+impl Asset for SceneFile {
+    type Settings = ();
+    type Loaded = SceneFile;
+
+    fn load(path: &Path, _settings: &()) -> Result<SceneFile, AssetError> {
+        SceneFile::load(path)
+    }
+
+    fn create(file: SceneFile, _device: &wgpu::Device, _queue: &wgpu::Queue) -> Self {
+        file
+    }
+}
@@ -0,0 +1,237 @@
+use glam::{Mat4, Vec3};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    // Élément neutre de `union`, `is_empty` renvoie true
+    pub const EMPTY: Self = Self {
+        min: Vec3::splat(f32::INFINITY),
+        max: Vec3::splat(f32::NEG_INFINITY),
+    };
+
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center_extents(center: Vec3, extents: Vec3) -> Self {
+        Self::new(center - extents, center + extents)
+    }
+
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self {
+        points.into_iter().fold(Self::EMPTY, |aabb, point| aabb.grow(point))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.cmpgt(self.max).any()
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    // Demi-taille sur chaque axe
+    pub fn extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    pub fn grow(self, point: Vec3) -> Self {
+        Self::new(self.min.min(point), self.max.max(point))
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min.cmple(other.max).all() && self.max.cmpge(other.min).all()
+    }
+
+    // AABB de la boîte transformée (méthode d'Arvo), plus large que la boîte
+    // d'origine si la matrice contient une rotation
+    pub fn transform(&self, matrix: Mat4) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+
+        let center = matrix.transform_point3(self.center());
+        let extents = self.extents();
+        let world_extents = matrix.x_axis.truncate().abs() * extents.x
+            + matrix.y_axis.truncate().abs() * extents.y
+            + matrix.z_axis.truncate().abs() * extents.z;
+
+        Self::from_center_extents(center, world_extents)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    // Algorithme de Ritter : pas minimal, au pire ~5% plus grand, mais en O(n)
+    pub fn from_points(points: &[Vec3]) -> Self {
+        let Some(&first) = points.first() else {
+            return Self::new(Vec3::ZERO, 0.0);
+        };
+
+        let farthest_from = |origin: Vec3| {
+            points
+                .iter()
+                .copied()
+                .max_by(|a, b| a.distance_squared(origin).total_cmp(&b.distance_squared(origin)))
+                .unwrap()
+        };
+
+        let a = farthest_from(first);
+        let b = farthest_from(a);
+        let mut sphere = Self::new((a + b) * 0.5, a.distance(b) * 0.5);
+
+        for &point in points {
+            let distance = point.distance(sphere.center);
+
+            if distance > sphere.radius {
+                let radius = (sphere.radius + distance) * 0.5;
+                sphere.center += (point - sphere.center) * ((radius - sphere.radius) / distance);
+                sphere.radius = radius;
+            }
+        }
+
+        sphere
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        point.distance_squared(self.center) <= self.radius * self.radius
+    }
+
+    // Le rayon est multiplié par la plus grande échelle de la matrice
+    pub fn transform(&self, matrix: Mat4) -> Self {
+        let scale = matrix
+            .x_axis
+            .truncate()
+            .length_squared()
+            .max(matrix.y_axis.truncate().length_squared())
+            .max(matrix.z_axis.truncate().length_squared())
+            .sqrt();
+
+        Self::new(matrix.transform_point3(self.center), self.radius * scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Quat, Vec3};
+
+    use super::*;
+
+    // Points pseudo-aléatoires reproductibles
+    fn points(count: usize) -> Vec<Vec3> {
+        let mut seed = 7u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0
+        };
+
+        (0..count).map(|_| Vec3::new(next() * 3.0, next(), next() * 0.5)).collect()
+    }
+
+    fn corners(aabb: &Aabb) -> [Vec3; 8] {
+        [0, 1, 2, 3, 4, 5, 6, 7].map(|i| {
+            Vec3::select(
+                glam::BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0),
+                aabb.max,
+                aabb.min,
+            )
+        })
+    }
+
+    #[test]
+    fn transform_matches_transformed_corners() {
+        let aabb = Aabb::new(Vec3::new(-1.0, 0.0, 2.0), Vec3::new(3.0, 0.5, 4.0));
+        let matrices = [
+            Mat4::IDENTITY,
+            Mat4::from_translation(Vec3::new(1.0, -2.0, 3.0)),
+            Mat4::from_scale_rotation_translation(
+                Vec3::new(2.0, 0.5, -1.0),
+                Quat::from_euler(glam::EulerRot::YXZ, 0.3, -1.2, 2.0),
+                Vec3::new(-4.0, 1.0, 0.5),
+            ),
+        ];
+
+        for matrix in matrices {
+            let expected = Aabb::from_points(corners(&aabb).map(|corner| matrix.transform_point3(corner)));
+            let transformed = aabb.transform(matrix);
+
+            assert!(transformed.min.abs_diff_eq(expected.min, 1e-4), "{matrix}");
+            assert!(transformed.max.abs_diff_eq(expected.max, 1e-4), "{matrix}");
+        }
+    }
+
+    #[test]
+    fn transform_keeps_empty_boxes_empty() {
+        assert!(Aabb::EMPTY.transform(Mat4::from_translation(Vec3::ONE)).is_empty());
+    }
+
+    #[test]
+    fn from_points_matches_brute_force() {
+        let points = points(100);
+        let aabb = Aabb::from_points(points.iter().copied());
+
+        for axis in 0..3 {
+            let values = points.iter().map(|point| point[axis]);
+            assert_eq!(aabb.min[axis], values.clone().fold(f32::INFINITY, f32::min));
+            assert_eq!(aabb.max[axis], values.fold(f32::NEG_INFINITY, f32::max));
+        }
+    }
+
+    #[test]
+    fn ritter_sphere_contains_every_point() {
+        let points = points(200);
+        let sphere = BoundingSphere::from_points(&points);
+
+        for &point in &points {
+            assert!(point.distance(sphere.center) <= sphere.radius * (1.0 + 1e-5), "{point}");
+        }
+
+        // Aucune sphère ne peut être plus petite que la moitié du diamètre de
+        // l'ensemble, et la sphère minimale n'est jamais plus grande que
+        // sqrt(3/8) fois ce diamètre (théorème de Jung)
+        let diameter = points
+            .iter()
+            .flat_map(|a| points.iter().map(move |b| a.distance(*b)))
+            .fold(0.0, f32::max);
+
+        assert!(sphere.radius >= diameter * 0.5 - 1e-5);
+        assert!(sphere.radius <= diameter * (3.0f32 / 8.0).sqrt() * 1.1);
+    }
+
+    #[test]
+    fn ritter_sphere_of_a_segment() {
+        let sphere = BoundingSphere::from_points(&[Vec3::ZERO, Vec3::new(4.0, 0.0, 0.0), Vec3::new(2.0, 1.0, 0.0)]);
+
+        assert!(sphere.center.abs_diff_eq(Vec3::new(2.0, 0.0, 0.0), 1e-5));
+        assert!((sphere.radius - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn empty_sphere() {
+        assert_eq!(BoundingSphere::from_points(&[]), BoundingSphere::new(Vec3::ZERO, 0.0));
+    }
+}
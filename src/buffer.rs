@@ -0,0 +1,112 @@
+use std::ops::Range;
+
+// Buffer GPU dont le nombre d'éléments change d'une frame à l'autre. Il double
+// de taille quand il est trop petit, et seuls les éléments modifiés depuis
+// le dernier envoi sont réécrits.
+pub struct DynamicBuffer<T> {
+    label: String,
+    usage: wgpu::BufferUsages,
+    buffer: wgpu::Buffer,
+    capacity: usize,
+    // Copie de ce qui est sur le GPU, pour trouver ce qui a changé
+    data: Vec<T>,
+    version: u32,
+}
+
+impl<T: bytemuck::Pod> DynamicBuffer<T> {
+    // wgpu n'accepte que des copies alignées sur 4 octets
+    pub fn new(device: &wgpu::Device, label: &str, usage: wgpu::BufferUsages, capacity: usize) -> Self {
+        assert!(
+            (std::mem::size_of::<T>() as wgpu::BufferAddress).is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT),
+            "DynamicBuffer element size must be a multiple of 4 bytes"
+        );
+
+        let usage = usage | wgpu::BufferUsages::COPY_DST;
+        let capacity = capacity.max(1);
+
+        Self {
+            label: label.to_owned(),
+            usage,
+            buffer: create_buffer::<T>(device, label, usage, capacity),
+            capacity,
+            data: Vec::new(),
+            version: 0,
+        }
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    // Augmente à chaque fois que le buffer est recréé, les bind groups qui
+    // l'utilisent doivent alors l'être aussi
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Renvoie true si le buffer a été recréé
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, items: &[T]) -> bool {
+        let grown = items.len() > self.capacity;
+
+        if grown {
+            self.capacity = items.len().max(self.capacity * 2);
+            self.buffer = create_buffer::<T>(device, &self.label, self.usage, self.capacity);
+            self.version += 1;
+
+            queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(items));
+        } else {
+            for range in changed_ranges(&self.data, items) {
+                let offset = (range.start * std::mem::size_of::<T>()) as wgpu::BufferAddress;
+                queue.write_buffer(&self.buffer, offset, bytemuck::cast_slice(&items[range]));
+            }
+        }
+
+        self.data.clear();
+        self.data.extend_from_slice(items);
+        grown
+    }
+}
+
+fn create_buffer<T>(device: &wgpu::Device, label: &str, usage: wgpu::BufferUsages, capacity: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: (capacity * std::mem::size_of::<T>()) as wgpu::BufferAddress,
+        usage,
+        mapped_at_creation: false,
+    })
+}
+
+// Suites d'éléments différents entre `old` et `new`, les éléments au-delà de
+// la fin de `old` comptent comme modifiés
+fn changed_ranges<T: bytemuck::Pod>(old: &[T], new: &[T]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+
+    for (index, item) in new.iter().enumerate() {
+        let changed = old
+            .get(index)
+            .is_none_or(|old_item| bytemuck::bytes_of(old_item) != bytemuck::bytes_of(item));
+
+        if !changed {
+            continue;
+        }
+
+        match ranges.last_mut() {
+            Some(range) if range.end == index => range.end += 1,
+            _ => ranges.push(index..index + 1),
+        }
+    }
+
+    ranges
+}
@@ -0,0 +1,255 @@
+use glam::Vec3;
+
+use crate::{
+    bounds::Aabb,
+    mesh::Mesh,
+    ray::{Ray, RayHit},
+};
+
+// Au-delà, une feuille est découpée en deux
+const MAX_LEAF_TRIANGLES: usize = 4;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TriangleHit {
+    pub distance: f32,
+    pub normal: Vec3,
+    // Indice du triangle dans le mesh d'origine
+    pub triangle: u32,
+}
+
+// Hiérarchie de boîtes sur les triangles d'un mesh, pour ne tester que ceux
+// dont les boîtes sont traversées par le rayon
+#[derive(Clone, Debug)]
+pub struct MeshBvh {
+    positions: Vec<Vec3>,
+    triangles: Vec<[u32; 3]>,
+    // Triangles réordonnés pour que chaque feuille en couvre une plage contiguë
+    order: Vec<u32>,
+    nodes: Vec<BvhNode>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct BvhNode {
+    aabb: Aabb,
+    // Feuille : plage `first..first + count` de `order`. Noeud interne
+    // (`count` nul) : enfants en `first` et `first + 1`.
+    first: u32,
+    count: u32,
+}
+
+impl MeshBvh {
+    pub fn new(mesh: &Mesh) -> Self {
+        let positions = mesh.vertices.iter().map(|vertex| Vec3::from(vertex.position)).collect();
+        Self::from_triangles(positions, &mesh.indices)
+    }
+
+    pub fn from_triangles(positions: Vec<Vec3>, indices: &[u32]) -> Self {
+        let triangles: Vec<[u32; 3]> = indices
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .collect();
+
+        let centroids: Vec<Vec3> = triangles
+            .iter()
+            .map(|&[a, b, c]| (positions[a as usize] + positions[b as usize] + positions[c as usize]) / 3.0)
+            .collect();
+
+        let mut bvh = Self {
+            order: (0..triangles.len() as u32).collect(),
+            positions,
+            triangles,
+            nodes: Vec::new(),
+        };
+
+        bvh.nodes.push(BvhNode {
+            aabb: Aabb::EMPTY,
+            first: 0,
+            count: bvh.triangles.len() as u32,
+        });
+        bvh.subdivide(0, &centroids);
+
+        bvh
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        self.nodes[0].aabb
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    pub fn raycast(&self, ray: &Ray) -> Option<TriangleHit> {
+        if self.triangles.is_empty() {
+            return None;
+        }
+
+        let mut closest: Option<TriangleHit> = None;
+        let mut stack = vec![0];
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+
+            match ray.aabb_distance(&node.aabb) {
+                Some(distance) if closest.is_none_or(|hit| distance <= hit.distance) => {}
+                _ => continue,
+            }
+
+            if node.count == 0 {
+                stack.push(node.first as usize);
+                stack.push(node.first as usize + 1);
+                continue;
+            }
+
+            for &triangle in &self.order[node.first as usize..(node.first + node.count) as usize] {
+                let [a, b, c] = self.triangle(triangle);
+
+                if let Some(RayHit { distance, normal }) = ray.intersect_triangle(a, b, c) {
+                    if closest.is_none_or(|hit| distance < hit.distance) {
+                        closest = Some(TriangleHit {
+                            distance,
+                            normal,
+                            triangle,
+                        });
+                    }
+                }
+            }
+        }
+
+        closest
+    }
+
+    fn triangle(&self, index: u32) -> [Vec3; 3] {
+        self.triangles[index as usize].map(|vertex| self.positions[vertex as usize])
+    }
+
+    // Coupe au milieu de l'axe le plus long des centres des triangles
+    fn subdivide(&mut self, node_index: usize, centroids: &[Vec3]) {
+        let BvhNode { first, count, .. } = self.nodes[node_index];
+        let range = first as usize..(first + count) as usize;
+
+        self.nodes[node_index].aabb = Aabb::from_points(
+            self.order[range.clone()]
+                .iter()
+                .flat_map(|&triangle| self.triangle(triangle)),
+        );
+
+        if range.len() <= MAX_LEAF_TRIANGLES {
+            return;
+        }
+
+        let centroid_bounds = Aabb::from_points(self.order[range.clone()].iter().map(|&t| centroids[t as usize]));
+        let size = centroid_bounds.size();
+        let axis = if size.x >= size.y && size.x >= size.z {
+            0
+        } else if size.y >= size.z {
+            1
+        } else {
+            2
+        };
+
+        // Tous les centres confondus, impossible de les séparer
+        if size[axis] <= 0.0 {
+            return;
+        }
+
+        let split = centroid_bounds.center()[axis];
+        let triangles = &mut self.order[range.clone()];
+        let mut left_count = 0;
+        for i in 0..triangles.len() {
+            if centroids[triangles[i] as usize][axis] < split {
+                triangles.swap(i, left_count);
+                left_count += 1;
+            }
+        }
+
+        // Répartition déséquilibrée, on coupe à la médiane
+        if left_count == 0 || left_count == triangles.len() {
+            left_count = triangles.len() / 2;
+            triangles.select_nth_unstable_by(left_count, |a, b| {
+                centroids[*a as usize][axis].total_cmp(&centroids[*b as usize][axis])
+            });
+        }
+
+        let left = self.nodes.len();
+        self.nodes.push(BvhNode {
+            aabb: Aabb::EMPTY,
+            first,
+            count: left_count as u32,
+        });
+        self.nodes.push(BvhNode {
+            aabb: Aabb::EMPTY,
+            first: first + left_count as u32,
+            count: count - left_count as u32,
+        });
+
+        self.nodes[node_index].first = left as u32;
+        self.nodes[node_index].count = 0;
+
+        self.subdivide(left, centroids);
+        self.subdivide(left + 1, centroids);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::primitives;
+
+    // Le triangle le plus proche en testant tous les triangles
+    fn brute_force(bvh: &MeshBvh, ray: &Ray) -> Option<RayHit> {
+        (0..bvh.triangle_count() as u32)
+            .filter_map(|triangle| {
+                let [a, b, c] = bvh.triangle(triangle);
+                ray.intersect_triangle(a, b, c)
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    #[test]
+    fn raycast_matches_brute_force_on_a_sphere() {
+        let sphere = primitives::uv_sphere(1.0, 24, 12);
+        let bvh = MeshBvh::new(&sphere);
+        assert_eq!(bvh.triangle_count(), sphere.indices.len() / 3);
+
+        let mut seed = 3u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0
+        };
+
+        let mut hits = 0;
+
+        for i in 0..500 {
+            // Un rayon sur cinq part de l'intérieur
+            let origin = if i % 5 == 0 {
+                Vec3::new(next(), next(), next()) * 0.5
+            } else {
+                (Vec3::new(next(), next(), next()) + Vec3::splat(0.01)).normalize() * 3.0
+            };
+            let target = Vec3::new(next(), next(), next()) * 1.5;
+            let ray = Ray::new(origin, target - origin);
+
+            let expected = brute_force(&bvh, &ray);
+            let hit = bvh.raycast(&ray);
+            assert_eq!(hit.is_some(), expected.is_some(), "{ray:?}");
+
+            if let (Some(hit), Some(expected)) = (hit, expected) {
+                // Sur une arête commune, les deux triangles sont à la même distance
+                assert!((hit.distance - expected.distance).abs() < 1e-5, "{ray:?}");
+                let [a, b, c] = bvh.triangle(hit.triangle);
+                assert_eq!(ray.intersect_triangle(a, b, c).map(|hit| hit.distance), Some(hit.distance));
+                hits += 1;
+            }
+        }
+
+        // Les deux cas sont bien couverts
+        assert!(hits > 100 && hits < 500, "{hits}");
+    }
+
+    #[test]
+    fn raycast_on_an_empty_mesh() {
+        let bvh = MeshBvh::from_triangles(Vec::new(), &[]);
+        assert_eq!(bvh.raycast(&Ray::new(Vec3::ZERO, Vec3::X)), None);
+    }
+}
@@ -0,0 +1,119 @@
+use glam::{Mat4, Vec3};
+use serde::{Deserialize, Serialize};
+
+pub mod controller;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Projection {
+    Perspective { fov_y: f32, near: f32, far: f32 },
+    // La profondeur va de 1 au plan proche à 0 à l'infini. Les flottants étant
+    // plus précis près de 0, la précision est répartie bien plus également.
+    ReverseZInfinite { fov_y: f32, near: f32 },
+    // `height` est la hauteur visible en unités du monde, la largeur suit le ratio
+    Orthographic { height: f32, near: f32, far: f32 },
+}
+
+// Sens de la profondeur, le test et la valeur d'effacement du depth buffer en dépendent
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DepthMode {
+    Standard,
+    Reversed,
+}
+
+impl DepthMode {
+    pub fn compare_function(self) -> wgpu::CompareFunction {
+        match self {
+            Self::Standard => wgpu::CompareFunction::Less,
+            Self::Reversed => wgpu::CompareFunction::Greater,
+        }
+    }
+
+    // Valeur la plus lointaine
+    pub fn clear_value(self) -> f32 {
+        match self {
+            Self::Standard => 1.0,
+            Self::Reversed => 0.0,
+        }
+    }
+}
+
+// La position et l'orientation viennent de la transformation de l'entité.
+// La caméra regarde vers -Z.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Camera {
+    pub projection: Projection,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::perspective(60.0f32.to_radians(), 0.1, 100.0)
+    }
+}
+
+impl Camera {
+    pub fn perspective(fov_y: f32, near: f32, far: f32) -> Self {
+        Self {
+            projection: Projection::Perspective { fov_y, near, far },
+        }
+    }
+
+    pub fn reverse_z_infinite(fov_y: f32, near: f32) -> Self {
+        Self {
+            projection: Projection::ReverseZInfinite { fov_y, near },
+        }
+    }
+
+    pub fn orthographic(height: f32, near: f32, far: f32) -> Self {
+        Self {
+            projection: Projection::Orthographic { height, near, far },
+        }
+    }
+
+    pub fn projection_matrix(&self, aspect_ratio: f32) -> Mat4 {
+        match self.projection {
+            Projection::Perspective { fov_y, near, far } => Mat4::perspective_rh(fov_y, aspect_ratio, near, far),
+            Projection::ReverseZInfinite { fov_y, near } => Mat4::perspective_infinite_reverse_rh(fov_y, aspect_ratio, near),
+            Projection::Orthographic { height, near, far } => {
+                let half_height = height * 0.5;
+                let half_width = half_height * aspect_ratio;
+                Mat4::orthographic_rh(-half_width, half_width, -half_height, half_height, near, far)
+            }
+        }
+    }
+
+    pub fn depth_mode(&self) -> DepthMode {
+        match self.projection {
+            Projection::ReverseZInfinite { .. } => DepthMode::Reversed,
+            Projection::Perspective { .. } | Projection::Orthographic { .. } => DepthMode::Standard,
+        }
+    }
+
+    // `world_matrix` place la caméra dans le monde, la vue est son inverse
+    pub fn view_matrix(world_matrix: Mat4) -> Mat4 {
+        world_matrix.inverse()
+    }
+
+    pub fn uniform(&self, world_matrix: Mat4, aspect_ratio: f32) -> CameraUniform {
+        let view_proj = self.projection_matrix(aspect_ratio) * Self::view_matrix(world_matrix);
+        CameraUniform::new(view_proj, world_matrix.w_axis.truncate())
+    }
+}
+
+// Doit correspondre à la structure Camera des shaders (common.nzsl de l'app)
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraUniform {
+    pub view_proj_matrix: [f32; 16],
+    pub pos: [f32; 3],
+    _pad: u32,
+}
+
+impl CameraUniform {
+    pub fn new(view_proj: Mat4, position: Vec3) -> Self {
+        Self {
+            view_proj_matrix: view_proj.to_cols_array(),
+            pos: position.into(),
+            _pad: 0,
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use std::f32::consts::FRAC_PI_2;
+
+use glam::{EulerRot, Quat, Vec3};
+use winit::event::{MouseButton, VirtualKeyCode};
+
+use crate::{
+    ecs::{Component, World},
+    input::Input,
+    transform::Transform,
+};
+
+// Un peu moins que la verticale pour que la vue ne se retourne pas
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+// Déplace une caméra à partir des entrées. Le lissage fait que la caméra
+// rattrape progressivement la position visée au lieu d'y sauter.
+pub trait CameraController {
+    fn update(&mut self, input: &Input, dt: f32);
+    fn transform(&self) -> Transform;
+}
+
+// Met à jour les contrôleurs de type `C` et recopie leur résultat dans le
+// `Transform` de leur entité
+pub fn update_controllers<C: CameraController + Component>(world: &World, input: &Input, dt: f32) {
+    world.query::<(&mut C, &mut Transform)>(|_, (controller, transform)| {
+        controller.update(input, dt);
+        *transform = controller.transform();
+    });
+}
+
+// `smoothing` est le temps en secondes pour parcourir ~63% du chemin, 0 pour aucun lissage
+fn smoothing_factor(smoothing: f32, dt: f32) -> f32 {
+    if smoothing > 0.0 {
+        1.0 - (-dt / smoothing).exp()
+    } else {
+        1.0
+    }
+}
+
+fn rotation(yaw: f32, pitch: f32) -> Quat {
+    Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0)
+}
+
+fn yaw_pitch(rotation: Quat) -> (f32, f32) {
+    let (yaw, pitch, _) = rotation.to_euler(EulerRot::YXZ);
+    (yaw, pitch.clamp(-MAX_PITCH, MAX_PITCH))
+}
+
+// Rotation à la souris tant que le bouton droit est enfoncé
+fn mouse_look(input: &Input, look_speed: f32, yaw: &mut f32, pitch: &mut f32) {
+    if input.button_down(MouseButton::Right) {
+        let delta = input.cursor_delta();
+        *yaw -= delta.x * look_speed;
+        *pitch = (*pitch - delta.y * look_speed).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+}
+
+// Bouton gauche pour tourner autour de la cible, droit ou milieu pour la
+// déplacer, molette pour s'approcher
+#[derive(Clone, Debug)]
+pub struct OrbitController {
+    pub target: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    // Radians par pixel
+    pub rotate_speed: f32,
+    // Fraction de la distance par cran de molette
+    pub zoom_speed: f32,
+    // Fraction de la distance par pixel
+    pub pan_speed: f32,
+    // Radians par seconde quand la caméra n'est pas manipulée
+    pub auto_rotate: f32,
+    pub smoothing: f32,
+    current: OrbitState,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct OrbitState {
+    target: Vec3,
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+}
+
+impl OrbitController {
+    pub fn new(target: Vec3, distance: f32) -> Self {
+        Self {
+            target,
+            yaw: 0.0,
+            pitch: 0.0,
+            distance,
+            min_distance: 0.5,
+            max_distance: 100.0,
+            rotate_speed: 0.005,
+            zoom_speed: 0.1,
+            pan_speed: 0.001,
+            auto_rotate: 0.0,
+            smoothing: 0.1,
+            current: OrbitState {
+                target,
+                yaw: 0.0,
+                pitch: 0.0,
+                distance,
+            },
+        }
+    }
+
+    // Tourne autour du point à `distance` devant la caméra
+    pub fn from_transform(transform: &Transform, distance: f32) -> Self {
+        let (yaw, pitch) = yaw_pitch(transform.rotation);
+        let target = transform.translation + transform.rotation * Vec3::NEG_Z * distance;
+
+        Self::new(target, distance).with_angles(yaw, pitch)
+    }
+
+    pub fn with_angles(mut self, yaw: f32, pitch: f32) -> Self {
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+        self.current.yaw = self.yaw;
+        self.current.pitch = self.pitch;
+        self
+    }
+
+    pub fn with_auto_rotate(mut self, speed: f32) -> Self {
+        self.auto_rotate = speed;
+        self
+    }
+
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+}
+
+impl CameraController for OrbitController {
+    fn update(&mut self, input: &Input, dt: f32) {
+        let delta = input.cursor_delta();
+
+        if input.button_down(MouseButton::Left) {
+            self.yaw -= delta.x * self.rotate_speed;
+            self.pitch = (self.pitch - delta.y * self.rotate_speed).clamp(-MAX_PITCH, MAX_PITCH);
+        } else {
+            self.yaw += self.auto_rotate * dt;
+        }
+
+        if input.button_down(MouseButton::Right) || input.button_down(MouseButton::Middle) {
+            let rotation = rotation(self.current.yaw, self.current.pitch);
+            let pan = rotation * Vec3::new(-delta.x, delta.y, 0.0);
+            self.target += pan * self.pan_speed * self.distance;
+        }
+
+        self.distance = (self.distance * (1.0 - self.zoom_speed).powf(input.scroll_delta()))
+            .clamp(self.min_distance, self.max_distance);
+
+        let t = smoothing_factor(self.smoothing, dt);
+        self.current = OrbitState {
+            target: self.current.target.lerp(self.target, t),
+            yaw: self.current.yaw + (self.yaw - self.current.yaw) * t,
+            pitch: self.current.pitch + (self.pitch - self.current.pitch) * t,
+            distance: self.current.distance + (self.distance - self.current.distance) * t,
+        };
+    }
+
+    fn transform(&self) -> Transform {
+        let rotation = rotation(self.current.yaw, self.current.pitch);
+
+        Transform {
+            translation: self.current.target + rotation * Vec3::Z * self.current.distance,
+            rotation,
+            scale: Vec3::ONE,
+        }
+    }
+}
+
+// Déplacement libre dans la direction du regard : WASD, Q et E pour
+// descendre et monter, Shift pour accélérer, bouton droit pour regarder
+#[derive(Clone, Debug)]
+pub struct FlyController {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    // Unités par seconde
+    pub speed: f32,
+    pub boost: f32,
+    // Radians par pixel
+    pub look_speed: f32,
+    pub smoothing: f32,
+    current: LookState,
+}
+
+// Position et orientation lissées des caméras à la première personne
+#[derive(Copy, Clone, Debug)]
+struct LookState {
+    position: Vec3,
+    yaw: f32,
+    pitch: f32,
+}
+
+impl LookState {
+    fn follow(&mut self, position: Vec3, yaw: f32, pitch: f32, t: f32) {
+        self.position = self.position.lerp(position, t);
+        self.yaw += (yaw - self.yaw) * t;
+        self.pitch += (pitch - self.pitch) * t;
+    }
+
+    fn transform(&self) -> Transform {
+        Transform {
+            translation: self.position,
+            rotation: rotation(self.yaw, self.pitch),
+            scale: Vec3::ONE,
+        }
+    }
+}
+
+impl FlyController {
+    pub fn new(position: Vec3, yaw: f32, pitch: f32) -> Self {
+        let pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+
+        Self {
+            position,
+            yaw,
+            pitch,
+            speed: 5.0,
+            boost: 4.0,
+            look_speed: 0.003,
+            smoothing: 0.08,
+            current: LookState { position, yaw, pitch },
+        }
+    }
+
+    pub fn from_transform(transform: &Transform) -> Self {
+        let (yaw, pitch) = yaw_pitch(transform.rotation);
+        Self::new(transform.translation, yaw, pitch)
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+}
+
+impl CameraController for FlyController {
+    fn update(&mut self, input: &Input, dt: f32) {
+        mouse_look(input, self.look_speed, &mut self.yaw, &mut self.pitch);
+
+        let rotation = rotation(self.yaw, self.pitch);
+        let direction = rotation * Vec3::X * input.axis(VirtualKeyCode::A, VirtualKeyCode::D)
+            + Vec3::Y * input.axis(VirtualKeyCode::Q, VirtualKeyCode::E)
+            + rotation * Vec3::NEG_Z * input.axis(VirtualKeyCode::S, VirtualKeyCode::W);
+
+        let speed = if input.key_down(VirtualKeyCode::LShift) {
+            self.speed * self.boost
+        } else {
+            self.speed
+        };
+
+        self.position += direction.normalize_or_zero() * speed * dt;
+
+        let t = smoothing_factor(self.smoothing, dt);
+        self.current.follow(self.position, self.yaw, self.pitch, t);
+    }
+
+    fn transform(&self) -> Transform {
+        self.current.transform()
+    }
+}
+
+// Comme `FlyController` mais le déplacement reste horizontal, à hauteur
+// d'yeux constante, quel que soit le regard
+#[derive(Clone, Debug)]
+pub struct FpsController {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub speed: f32,
+    pub run_multiplier: f32,
+    pub look_speed: f32,
+    pub smoothing: f32,
+    current: LookState,
+}
+
+impl FpsController {
+    pub fn new(position: Vec3, yaw: f32, pitch: f32) -> Self {
+        let pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+
+        Self {
+            position,
+            yaw,
+            pitch,
+            speed: 3.0,
+            run_multiplier: 2.0,
+            look_speed: 0.003,
+            smoothing: 0.05,
+            current: LookState { position, yaw, pitch },
+        }
+    }
+
+    pub fn from_transform(transform: &Transform) -> Self {
+        let (yaw, pitch) = yaw_pitch(transform.rotation);
+        Self::new(transform.translation, yaw, pitch)
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+}
+
+impl CameraController for FpsController {
+    fn update(&mut self, input: &Input, dt: f32) {
+        mouse_look(input, self.look_speed, &mut self.yaw, &mut self.pitch);
+
+        // Seul le cap compte pour se déplacer
+        let heading = Quat::from_rotation_y(self.yaw);
+        let direction = heading * Vec3::X * input.axis(VirtualKeyCode::A, VirtualKeyCode::D)
+            + heading * Vec3::NEG_Z * input.axis(VirtualKeyCode::S, VirtualKeyCode::W);
+
+        let speed = if input.key_down(VirtualKeyCode::LShift) {
+            self.speed * self.run_multiplier
+        } else {
+            self.speed
+        };
+
+        self.position += direction.normalize_or_zero() * speed * dt;
+
+        let t = smoothing_factor(self.smoothing, dt);
+        self.current.follow(self.position, self.yaw, self.pitch, t);
+    }
+
+    fn transform(&self) -> Transform {
+        self.current.transform()
+    }
+}
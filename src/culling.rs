@@ -0,0 +1,209 @@
+use glam::{Mat4, Vec3, Vec4};
+
+use crate::bounds::{Aabb, BoundingSphere};
+
+// Les points du côté positif, `normal.dot(p) + distance >= 0`, sont à l'intérieur
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Plane {
+    pub normal: Vec3,
+    pub distance: f32,
+}
+
+impl Plane {
+    pub fn new(normal: Vec3, distance: f32) -> Self {
+        Self { normal, distance }
+    }
+
+    // `coefficients` = (a, b, c, d) du plan ax + by + cz + d = 0. Une normale
+    // nulle, comme le plan lointain d'une projection infinie, donne un plan
+    // qui contient tout l'espace si d est positif.
+    pub fn from_coefficients(coefficients: Vec4) -> Self {
+        let length = coefficients.truncate().length();
+
+        if length > 0.0 {
+            Self::new(coefficients.truncate() / length, coefficients.w / length)
+        } else {
+            Self::new(Vec3::ZERO, coefficients.w)
+        }
+    }
+
+    pub fn signed_distance(&self, point: Vec3) -> f32 {
+        self.normal.dot(point) + self.distance
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Frustum {
+    // Gauche, droite, bas, haut, proche, lointain. Proche et lointain sont
+    // inversés avec une profondeur inversée.
+    pub planes: [Plane; 6],
+}
+
+impl Frustum {
+    // Méthode de Gribb et Hartmann. La profondeur va de 0 à 1 comme dans wgpu,
+    // le plan proche est donc z >= 0 et pas z >= -w.
+    pub fn from_view_proj(view_proj: Mat4) -> Self {
+        let row0 = view_proj.row(0);
+        let row1 = view_proj.row(1);
+        let row2 = view_proj.row(2);
+        let row3 = view_proj.row(3);
+
+        Self {
+            planes: [
+                Plane::from_coefficients(row3 + row0),
+                Plane::from_coefficients(row3 - row0),
+                Plane::from_coefficients(row3 + row1),
+                Plane::from_coefficients(row3 - row1),
+                Plane::from_coefficients(row2),
+                Plane::from_coefficients(row3 - row2),
+            ],
+        }
+    }
+
+    pub fn contains_point(&self, point: Vec3) -> bool {
+        self.planes.iter().all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes.iter().all(|plane| plane.signed_distance(sphere.center) >= -sphere.radius)
+    }
+
+    // Conservateur : une boîte proche d'un coin du frustum peut être gardée
+    // alors qu'elle est en dehors
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        if aabb.is_empty() {
+            return false;
+        }
+
+        self.planes.iter().all(|plane| {
+            // Le coin le plus loin dans la direction de la normale
+            let corner = Vec3::select(plane.normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+            plane.signed_distance(corner) >= 0.0
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CullStats {
+    pub visible: u32,
+    pub culled: u32,
+}
+
+impl CullStats {
+    pub fn total(&self) -> u32 {
+        self.visible + self.culled
+    }
+
+    pub fn record(&mut self, visible: bool) {
+        if visible {
+            self.visible += 1;
+        } else {
+            self.culled += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    // La caméra est à l'origine et regarde vers -Z, avec 90° d'ouverture ou
+    // une demi-largeur de 10 : à 10 unités, le bord droit est en x = 10
+    fn projections() -> [(&'static str, Mat4); 3] {
+        [
+            ("standard", Mat4::perspective_rh(FRAC_PI_2, 1.0, 0.1, 100.0)),
+            ("reverse z infinite", Mat4::perspective_infinite_reverse_rh(FRAC_PI_2, 1.0, 0.1)),
+            ("orthographic", Mat4::orthographic_rh(-10.0, 10.0, -10.0, 10.0, 0.1, 100.0)),
+        ]
+    }
+
+    fn cube(center: Vec3) -> Aabb {
+        Aabb::from_center_extents(center, Vec3::splat(0.5))
+    }
+
+    #[test]
+    fn planes_are_normalized_and_face_inside() {
+        for (name, projection) in projections() {
+            let frustum = Frustum::from_view_proj(projection);
+
+            for plane in &frustum.planes {
+                let length = plane.normal.length();
+                assert!(length == 0.0 || (length - 1.0).abs() < 1e-5, "{name}");
+                assert!(plane.signed_distance(Vec3::new(0.0, 0.0, -10.0)) > 0.0, "{name}");
+            }
+        }
+    }
+
+    #[test]
+    fn near_and_far_planes() {
+        for (name, projection) in projections() {
+            let frustum = Frustum::from_view_proj(projection);
+
+            assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -0.05)), "{name}");
+            assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -0.2)), "{name}");
+            assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 1.0)), "{name}");
+        }
+
+        // Seule la projection infinie n'a pas de plan lointain
+        let [(_, standard), (_, infinite), (_, orthographic)] = projections();
+        let far_point = Vec3::new(0.0, 0.0, -1000.0);
+        assert!(!Frustum::from_view_proj(standard).contains_point(far_point));
+        assert!(Frustum::from_view_proj(infinite).contains_point(far_point));
+        assert!(!Frustum::from_view_proj(orthographic).contains_point(far_point));
+    }
+
+    #[test]
+    fn boxes_inside_outside_and_straddling() {
+        for (name, projection) in projections() {
+            let frustum = Frustum::from_view_proj(projection);
+
+            assert!(frustum.intersects_aabb(&cube(Vec3::new(0.0, 0.0, -10.0))), "{name}");
+            assert!(frustum.intersects_aabb(&cube(Vec3::new(-3.0, 4.0, -50.0))), "{name}");
+
+            // À cheval sur les plans gauche, haut et proche
+            assert!(frustum.intersects_aabb(&cube(Vec3::new(-10.0, 0.0, -10.0))), "{name}");
+            assert!(frustum.intersects_aabb(&cube(Vec3::new(0.0, 10.0, -10.0))), "{name}");
+            assert!(frustum.intersects_aabb(&cube(Vec3::new(0.0, 0.0, 0.0))), "{name}");
+
+            // Derrière la caméra, et bien à droite ou en dessous
+            assert!(!frustum.intersects_aabb(&cube(Vec3::new(0.0, 0.0, 10.0))), "{name}");
+            assert!(!frustum.intersects_aabb(&cube(Vec3::new(12.0, 0.0, -10.0))), "{name}");
+            assert!(!frustum.intersects_aabb(&cube(Vec3::new(0.0, -12.0, -10.0))), "{name}");
+            assert!(!frustum.intersects_aabb(&Aabb::EMPTY), "{name}");
+        }
+    }
+
+    #[test]
+    fn boxes_seen_through_a_view_matrix() {
+        let view = Mat4::look_at_rh(Vec3::new(0.0, 0.0, 20.0), Vec3::ZERO, Vec3::Y);
+
+        for (name, projection) in projections() {
+            let frustum = Frustum::from_view_proj(projection * view);
+
+            assert!(frustum.intersects_aabb(&cube(Vec3::ZERO)), "{name}");
+            assert!(!frustum.intersects_aabb(&cube(Vec3::new(0.0, 0.0, 30.0))), "{name}");
+        }
+    }
+
+    #[test]
+    fn spheres() {
+        let frustum = Frustum::from_view_proj(Mat4::orthographic_rh(-10.0, 10.0, -10.0, 10.0, 0.1, 100.0));
+
+        assert!(frustum.intersects_sphere(&BoundingSphere::new(Vec3::new(10.5, 0.0, -10.0), 1.0)));
+        assert!(!frustum.intersects_sphere(&BoundingSphere::new(Vec3::new(11.5, 0.0, -10.0), 1.0)));
+    }
+
+    #[test]
+    fn stats_count_each_instance() {
+        let mut stats = CullStats::default();
+
+        for visible in [true, false, true] {
+            stats.record(visible);
+        }
+
+        assert_eq!(stats, CullStats { visible: 2, culled: 1 });
+        assert_eq!(stats.total(), 3);
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    any::{Any, TypeId},
+    cell::{Ref, RefCell},
+    collections::HashMap,
+};
+
+use self::query::Query;
+
+pub mod hierarchy;
+pub mod picking;
+pub mod query;
+pub mod render;
+pub mod scene;
+
+// N'importe quel type peut servir de composant
+pub trait Component: 'static {}
+
+impl<T: 'static> Component for T {}
+
+// La génération distingue une entité supprimée de celle qui a réutilisé son
+// emplacement, un `Entity` gardé trop longtemps ne désigne donc plus rien
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    pub fn index(self) -> u32 {
+        self.index
+    }
+}
+
+struct EntitySlot {
+    generation: u32,
+    alive: bool,
+}
+
+// Un tableau par type de composant, indexé par l'emplacement de l'entité.
+// Le RefCell permet d'emprunter plusieurs types à la fois dans une requête.
+struct ComponentStorage<T> {
+    components: RefCell<Vec<Option<T>>>,
+}
+
+trait AnyStorage {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn remove(&mut self, index: usize);
+}
+
+impl<T: Component> AnyStorage for ComponentStorage<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn remove(&mut self, index: usize) {
+        if let Some(component) = self.components.get_mut().get_mut(index) {
+            *component = None;
+        }
+    }
+}
+
+pub struct World {
+    entities: Vec<EntitySlot>,
+    free_slots: Vec<u32>,
+    storages: HashMap<TypeId, Box<dyn AnyStorage>>,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self {
+            entities: Vec::new(),
+            free_slots: Vec::new(),
+            storages: HashMap::new(),
+        }
+    }
+
+    pub fn spawn(&mut self) -> Entity {
+        match self.free_slots.pop() {
+            Some(index) => {
+                let slot = &mut self.entities[index as usize];
+                slot.alive = true;
+
+                Entity {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.entities.push(EntitySlot {
+                    generation: 0,
+                    alive: true,
+                });
+
+                Entity {
+                    index: self.entities.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    // Supprime aussi tous les composants de l'entité
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        for storage in self.storages.values_mut() {
+            storage.remove(entity.index as usize);
+        }
+
+        let slot = &mut self.entities[entity.index as usize];
+        slot.alive = false;
+        slot.generation += 1;
+        self.free_slots.push(entity.index);
+        true
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities
+            .get(entity.index as usize)
+            .is_some_and(|slot| slot.alive && slot.generation == entity.generation)
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.iter().enumerate().filter(|(_, slot)| slot.alive).map(|(index, slot)| Entity {
+            index: index as u32,
+            generation: slot.generation,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len() - self.free_slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Remplace le composant du même type s'il y en avait déjà un et le renvoie.
+    // Ne fait rien si l'entité n'existe plus.
+    pub fn insert<T: Component>(&mut self, entity: Entity, component: T) -> Option<T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        let components = self
+            .storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| {
+                Box::new(ComponentStorage::<T> {
+                    components: RefCell::new(Vec::new()),
+                })
+            })
+            .as_any_mut()
+            .downcast_mut::<ComponentStorage<T>>()
+            .unwrap()
+            .components
+            .get_mut();
+
+        let index = entity.index as usize;
+        if components.len() <= index {
+            components.resize_with(index + 1, || None);
+        }
+
+        components[index].replace(component)
+    }
+
+    pub fn remove<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        self.components_mut::<T>()?.get_mut(entity.index as usize)?.take()
+    }
+
+    // Panique si le type est déjà emprunté en écriture par une requête en cours
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<Ref<'_, T>> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        let components = self.components::<T>()?.borrow();
+        Ref::filter_map(components, |components| components.get(entity.index as usize)?.as_ref()).ok()
+    }
+
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<&mut T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        self.components_mut::<T>()?.get_mut(entity.index as usize)?.as_mut()
+    }
+
+    pub fn has<T: Component>(&self, entity: Entity) -> bool {
+        self.get::<T>(entity).is_some()
+    }
+
+    // Appelle `f` pour chaque entité qui a tous les composants demandés, ex:
+    // `world.query::<(&mut Transform, &Spin)>(|entity, (transform, spin)| ...)`.
+    // Demander deux fois le même type dont une en écriture panique.
+    pub fn query<Q: Query>(&self, mut f: impl FnMut(Entity, Q::Item<'_>)) {
+        let Some(mut borrow) = Q::borrow(self) else {
+            return;
+        };
+
+        for (index, slot) in self.entities.iter().enumerate() {
+            if !slot.alive {
+                continue;
+            }
+
+            if let Some(item) = Q::fetch(&mut borrow, index) {
+                let entity = Entity {
+                    index: index as u32,
+                    generation: slot.generation,
+                };
+
+                f(entity, item);
+            }
+        }
+    }
+
+    fn components<T: Component>(&self) -> Option<&RefCell<Vec<Option<T>>>> {
+        let storage = self.storages.get(&TypeId::of::<T>())?;
+        Some(&storage.as_any().downcast_ref::<ComponentStorage<T>>()?.components)
+    }
+
+    fn components_mut<T: Component>(&mut self) -> Option<&mut Vec<Option<T>>> {
+        let storage = self.storages.get_mut(&TypeId::of::<T>())?;
+        Some(storage.as_any_mut().downcast_mut::<ComponentStorage<T>>()?.components.get_mut())
+    }
+}
+
+// Un système est une fonction qui modifie le monde à chaque frame
+pub trait System {
+    fn run(&mut self, world: &mut World, dt: f32);
+}
+
+impl<F: FnMut(&mut World, f32)> System for F {
+    fn run(&mut self, world: &mut World, dt: f32) {
+        self(world, dt)
+    }
+}
+
+// Systèmes exécutés dans leur ordre d'ajout
+#[derive(Default)]
+pub struct Schedule {
+    systems: Vec<Box<dyn System>>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_system(mut self, system: impl System + 'static) -> Self {
+        self.add_system(system);
+        self
+    }
+
+    pub fn add_system(&mut self, system: impl System + 'static) {
+        self.systems.push(Box::new(system));
+    }
+
+    pub fn run(&mut self, world: &mut World, dt: f32) {
+        for system in &mut self.systems {
+            system.run(world, dt);
+        }
+    }
+}
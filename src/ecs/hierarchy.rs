@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+
+use glam::Mat4;
+
+use super::{Entity, World};
+use crate::transform::Transform;
+
+// Le `Transform` de l'entité devient relatif à celui de son parent
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Parent(pub Entity);
+
+// Transformation dans le monde mise en cache par `update_global_transforms`.
+// Elle n'est à jour qu'après l'appel, les modifications des `Transform` faites
+// ensuite n'y sont pas.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GlobalTransform {
+    pub matrix: Mat4,
+    // Ce qui a servi au dernier calcul, pour ne recalculer que les
+    // entités dont le `Transform` ou l'un des ancêtres a changé
+    local: Transform,
+    parent: Option<Entity>,
+}
+
+// À appeler une fois par frame, après les systèmes et la physique et avant
+// d'extraire la vue. Chaque entité n'est visitée qu'une fois, les parents
+// avant leurs enfants.
+pub fn update_global_transforms(world: &mut World) {
+    let mut entities = Vec::new();
+    world.query::<&Transform>(|entity, _| entities.push(entity));
+
+    let mut states = HashMap::with_capacity(entities.len());
+
+    for entity in entities {
+        update_global_transform(world, entity, &mut states);
+    }
+}
+
+#[derive(Copy, Clone)]
+enum VisitState {
+    // L'entité est un de ses propres ancêtres si on la recroise dans cet état
+    InProgress,
+    Done { changed: bool },
+}
+
+// Renvoie la matrice monde de l'entité et si elle a changé pendant cette mise à jour
+fn update_global_transform(
+    world: &mut World,
+    entity: Entity,
+    states: &mut HashMap<Entity, VisitState>,
+) -> Option<(Mat4, bool)> {
+    match states.get(&entity) {
+        Some(VisitState::Done { changed }) => {
+            return world.get::<GlobalTransform>(entity).map(|global| (global.matrix, *changed));
+        }
+        // Une hiérarchie cyclique est coupée ici
+        Some(VisitState::InProgress) => return None,
+        None => {}
+    }
+
+    let local = *world.get::<Transform>(entity)?;
+    states.insert(entity, VisitState::InProgress);
+
+    // Un parent supprimé ou sans `Transform` arrête la remontée
+    let parent = world.get::<Parent>(entity).map(|parent| parent.0);
+    let parent_global = parent.and_then(|parent| Some((parent, update_global_transform(world, parent, states)?)));
+    let parent = parent_global.map(|(parent, _)| parent);
+
+    let cached = world.get::<GlobalTransform>(entity).map(|global| *global);
+    let changed = match cached {
+        Some(cached) => {
+            cached.local != local || cached.parent != parent || parent_global.is_some_and(|(_, (_, changed))| changed)
+        }
+        None => true,
+    };
+
+    let matrix = if changed {
+        let parent_matrix = parent_global.map_or(Mat4::IDENTITY, |(_, (matrix, _))| matrix);
+        let matrix = parent_matrix * local.matrix();
+        world.insert(entity, GlobalTransform { matrix, local, parent });
+        matrix
+    } else {
+        cached.unwrap().matrix
+    };
+
+    states.insert(entity, VisitState::Done { changed });
+    Some((matrix, changed))
+}
+
+// Transformation dans le monde, lue dans le cache quand il existe. Une entité
+// créée depuis la dernière mise à jour remonte ses parents jusqu'au premier
+// qui en a un.
+pub fn world_matrix(world: &World, entity: Entity) -> Option<Mat4> {
+    if let Some(global) = world.get::<GlobalTransform>(entity) {
+        return Some(global.matrix);
+    }
+
+    let mut matrix = world.get::<Transform>(entity)?.matrix();
+    let mut current = entity;
+
+    // La limite évite de boucler sans fin sur une hiérarchie cyclique
+    for _ in 0..world.len() {
+        let Some(parent) = world.get::<Parent>(current).map(|parent| parent.0) else {
+            break;
+        };
+
+        if let Some(global) = world.get::<GlobalTransform>(parent) {
+            matrix = global.matrix * matrix;
+            break;
+        }
+
+        let Some(parent_transform) = world.get::<Transform>(parent).map(|transform| transform.matrix()) else {
+            break;
+        };
+
+        matrix = parent_transform * matrix;
+        current = parent;
+    }
+
+    Some(matrix)
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::*;
+
+    fn spawn(world: &mut World, x: f32, parent: Option<Entity>) -> Entity {
+        let entity = world.spawn();
+        world.insert(entity, Transform::from_translation(Vec3::new(x, 0.0, 0.0)));
+
+        if let Some(parent) = parent {
+            world.insert(entity, Parent(parent));
+        }
+
+        entity
+    }
+
+    fn global_x(world: &World, entity: Entity) -> f32 {
+        world.get::<GlobalTransform>(entity).unwrap().matrix.w_axis.x
+    }
+
+    #[test]
+    fn parents_propagate_to_children() {
+        let mut world = World::new();
+        let root = spawn(&mut world, 1.0, None);
+        let child = spawn(&mut world, 2.0, Some(root));
+        let grandchild = spawn(&mut world, 4.0, Some(child));
+
+        update_global_transforms(&mut world);
+        assert_eq!(global_x(&world, grandchild), 7.0);
+
+        // Seule la racine bouge, ses descendants doivent suivre
+        world.get_mut::<Transform>(root).unwrap().translation.x = -1.0;
+        update_global_transforms(&mut world);
+        assert_eq!(global_x(&world, child), 1.0);
+        assert_eq!(global_x(&world, grandchild), 5.0);
+        assert_eq!(world_matrix(&world, grandchild).unwrap().w_axis.x, 5.0);
+
+        // Changer de parent invalide aussi le cache
+        world.insert(grandchild, Parent(root));
+        update_global_transforms(&mut world);
+        assert_eq!(global_x(&world, grandchild), 3.0);
+    }
+
+    #[test]
+    fn removed_parents_and_cycles_stop_the_chain() {
+        let mut world = World::new();
+        let root = spawn(&mut world, 1.0, None);
+        let child = spawn(&mut world, 2.0, Some(root));
+        let a = spawn(&mut world, 3.0, None);
+        let b = spawn(&mut world, 5.0, Some(a));
+        world.insert(a, Parent(b));
+
+        update_global_transforms(&mut world);
+        world.despawn(root);
+        update_global_transforms(&mut world);
+
+        assert_eq!(global_x(&world, child), 2.0);
+        // Le cycle est coupé sur celle des deux entités visitée en second
+        let cycle = (global_x(&world, a), global_x(&world, b));
+        assert!(cycle == (3.0, 8.0) || cycle == (8.0, 5.0), "{cycle:?}");
+    }
+
+    #[test]
+    fn world_matrix_walks_up_to_the_cache() {
+        let mut world = World::new();
+        let root = spawn(&mut world, 1.0, None);
+        update_global_transforms(&mut world);
+        world.get_mut::<Transform>(root).unwrap().translation.x = 10.0;
+
+        // L'enfant créé après la mise à jour utilise le cache de son parent
+        let child = spawn(&mut world, 2.0, Some(root));
+        assert_eq!(world_matrix(&world, child).unwrap().w_axis.x, 3.0);
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use glam::{Mat4, Vec3};
+
+use super::{hierarchy::world_matrix, render::MeshRenderer, Entity, World};
+use crate::{
+    asset::AssetServer,
+    bounds::Aabb,
+    bvh::MeshBvh,
+    ray::{self, Ray, RayHit},
+    transform::Transform,
+};
+
+// Forme testée par `raycast`, dans l'espace local de l'entité. Sans
+// collider, une entité avec un `MeshRenderer` est testée contre la boîte
+// de son mesh.
+#[derive(Clone, Debug)]
+pub enum Collider {
+    // Reste alignée sur les axes du monde quand l'entité tourne
+    Aabb(Aabb),
+    // Tourne avec l'entité
+    Box(Aabb),
+    // Triangle par triangle. Le BVH peut être partagé entre plusieurs entités.
+    Mesh(Arc<MeshBvh>),
+}
+
+impl Collider {
+    fn intersect(&self, ray: &Ray, model_matrix: Mat4) -> Option<RayHit> {
+        match self {
+            Self::Aabb(aabb) => ray.intersect_aabb(&aabb.transform(model_matrix)),
+            Self::Box(aabb) => ray.intersect_box(aabb, model_matrix),
+            Self::Mesh(bvh) => {
+                let inverse = model_matrix.inverse();
+                let hit = bvh.raycast(&ray.transform(inverse))?;
+
+                Some(RayHit {
+                    distance: hit.distance,
+                    normal: ray::transform_normal(hit.normal, inverse),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PickHit {
+    pub entity: Entity,
+    pub distance: f32,
+    pub position: Vec3,
+    pub normal: Vec3,
+}
+
+// Entité la plus proche touchée par le rayon
+pub fn raycast(world: &World, assets: &AssetServer, ray: &Ray) -> Option<PickHit> {
+    let mut closest: Option<PickHit> = None;
+
+    let mut record = |entity: Entity, hit: Option<RayHit>| {
+        let Some(RayHit { distance, normal }) = hit else {
+            return;
+        };
+
+        if closest.is_none_or(|closest| distance < closest.distance) {
+            closest = Some(PickHit {
+                entity,
+                distance,
+                position: ray.at(distance),
+                normal,
+            });
+        }
+    };
+
+    world.query::<(&Transform, Option<&Collider>, Option<&MeshRenderer>)>(|entity, (_, collider, renderer)| {
+        let Some(model_matrix) = world_matrix(world, entity) else {
+            return;
+        };
+
+        match (collider, renderer) {
+            (Some(collider), _) => record(entity, collider.intersect(ray, model_matrix)),
+            (None, Some(renderer)) => {
+                // Tant que le mesh charge, on ne connaît pas sa taille
+                if let Some(mesh) = assets.get(&renderer.mesh) {
+                    record(entity, ray.intersect_box(&mesh.aabb, model_matrix));
+                }
+            }
+            (None, None) => {}
+        }
+    });
+
+    closest
+}
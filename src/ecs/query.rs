@@ -0,0 +1,77 @@
+use std::cell::{Ref, RefMut};
+
+use super::{Component, World};
+
+// Ce qu'on peut demander à `World::query` : `&T`, `&mut T`, `Option<&T>` ou un
+// tuple de ceux-ci. `borrow` emprunte les tableaux de composants une fois
+// pour toute la requête, `fetch` lit ceux d'une entité.
+pub trait Query {
+    type Borrow<'w>;
+    type Item<'b>;
+
+    fn borrow(world: &World) -> Option<Self::Borrow<'_>>;
+    fn fetch<'b>(borrow: &'b mut Self::Borrow<'_>, index: usize) -> Option<Self::Item<'b>>;
+}
+
+impl<T: Component> Query for &T {
+    type Borrow<'w> = Ref<'w, Vec<Option<T>>>;
+    type Item<'b> = &'b T;
+
+    fn borrow(world: &World) -> Option<Self::Borrow<'_>> {
+        Some(world.components::<T>()?.borrow())
+    }
+
+    fn fetch<'b>(borrow: &'b mut Self::Borrow<'_>, index: usize) -> Option<Self::Item<'b>> {
+        borrow.get(index)?.as_ref()
+    }
+}
+
+impl<T: Component> Query for &mut T {
+    type Borrow<'w> = RefMut<'w, Vec<Option<T>>>;
+    type Item<'b> = &'b mut T;
+
+    fn borrow(world: &World) -> Option<Self::Borrow<'_>> {
+        Some(world.components::<T>()?.borrow_mut())
+    }
+
+    fn fetch<'b>(borrow: &'b mut Self::Borrow<'_>, index: usize) -> Option<Self::Item<'b>> {
+        borrow.get_mut(index)?.as_mut()
+    }
+}
+
+// Ne filtre pas les entités, donne None quand le composant manque
+impl<T: Component> Query for Option<&T> {
+    type Borrow<'w> = Option<Ref<'w, Vec<Option<T>>>>;
+    type Item<'b> = Option<&'b T>;
+
+    fn borrow(world: &World) -> Option<Self::Borrow<'_>> {
+        Some(world.components::<T>().map(|components| components.borrow()))
+    }
+
+    fn fetch<'b>(borrow: &'b mut Self::Borrow<'_>, index: usize) -> Option<Self::Item<'b>> {
+        Some(borrow.as_ref().and_then(|components| components.get(index)?.as_ref()))
+    }
+}
+
+macro_rules! impl_query_for_tuple {
+    ($($name:ident: $index:tt),+) => {
+        impl<$($name: Query),+> Query for ($($name,)+) {
+            type Borrow<'w> = ($($name::Borrow<'w>,)+);
+            type Item<'b> = ($($name::Item<'b>,)+);
+
+            fn borrow(world: &World) -> Option<Self::Borrow<'_>> {
+                Some(($($name::borrow(world)?,)+))
+            }
+
+            fn fetch<'b>(borrow: &'b mut Self::Borrow<'_>, index: usize) -> Option<Self::Item<'b>> {
+                Some(($($name::fetch(&mut borrow.$index, index)?,)+))
+            }
+        }
+    };
+}
+
+impl_query_for_tuple!(A: 0);
+impl_query_for_tuple!(A: 0, B: 1);
+impl_query_for_tuple!(A: 0, B: 1, C: 2);
+impl_query_for_tuple!(A: 0, B: 1, C: 2, D: 3);
+impl_query_for_tuple!(A: 0, B: 1, C: 2, D: 3, E: 4);
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use glam::{Mat4, Vec2, Vec3};
+
+use super::{hierarchy::world_matrix, Entity, World};
+use crate::{
+    asset::{AssetServer, Handle},
+    camera::{Camera, CameraUniform, DepthMode},
+    culling::{CullStats, Frustum},
+    light::Light,
+    material::MaterialId,
+    mesh::GpuMesh,
+    ray::Ray,
+    transform::Transform,
+};
+
+// Composants reconnus par le rendu. Une entité n'est dessinée, éclairée ou
+// filmée que si elle a aussi un `Transform`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MeshRenderer {
+    pub mesh: Handle<GpuMesh>,
+    pub material: MaterialId,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MeshInstance {
+    pub entity: Entity,
+    pub model_matrix: Mat4,
+    pub material: MaterialId,
+    // Mis à jour par `RenderView::cull`
+    pub visible: bool,
+}
+
+// Instances qui partagent le même mesh, à dessiner en un seul appel
+#[derive(Clone, Debug)]
+pub struct MeshBatch {
+    pub mesh: Handle<GpuMesh>,
+    pub instances: Vec<MeshInstance>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ViewCamera {
+    pub entity: Entity,
+    pub position: Vec3,
+    pub view_matrix: Mat4,
+    pub projection_matrix: Mat4,
+    pub depth_mode: DepthMode,
+}
+
+impl ViewCamera {
+    pub fn view_proj_matrix(&self) -> Mat4 {
+        self.projection_matrix * self.view_matrix
+    }
+
+    pub fn uniform(&self) -> CameraUniform {
+        CameraUniform::new(self.view_proj_matrix(), self.position)
+    }
+
+    // Rayon sous le curseur, en pixels depuis le coin haut gauche. Il part du
+    // plan proche, ce qui marche aussi pour une projection orthographique.
+    pub fn screen_ray(&self, cursor: Vec2, viewport_size: Vec2) -> Ray {
+        let ndc = Vec2::new(
+            cursor.x / viewport_size.x * 2.0 - 1.0,
+            1.0 - cursor.y / viewport_size.y * 2.0,
+        );
+
+        // Avec une projection infinie la profondeur lointaine est à l'infini,
+        // une profondeur intermédiaire suffit pour la direction
+        let (near_depth, far_depth) = match self.depth_mode {
+            DepthMode::Standard => (0.0, 1.0),
+            DepthMode::Reversed => (1.0, 0.5),
+        };
+
+        let inverse = self.view_proj_matrix().inverse();
+        let near = inverse.project_point3(ndc.extend(near_depth));
+        let far = inverse.project_point3(ndc.extend(far_depth));
+
+        Ray::new(near, far - near)
+    }
+}
+
+// Tout ce dont le rendu a besoin pour une frame, extrait du monde
+#[derive(Clone, Debug, Default)]
+pub struct RenderView {
+    // La première entité avec une `Camera`
+    pub camera: Option<ViewCamera>,
+    pub batches: Vec<MeshBatch>,
+    // Position et direction dans le monde, tirées de la transformation
+    pub lights: Vec<(Entity, Light)>,
+}
+
+impl RenderView {
+    pub fn extract(world: &World, aspect_ratio: f32) -> Self {
+        let mut view = Self::default();
+
+        world.query::<(&Camera, &Transform)>(|entity, (camera, _)| {
+            if view.camera.is_some() {
+                return;
+            }
+
+            if let Some(matrix) = world_matrix(world, entity) {
+                view.camera = Some(ViewCamera {
+                    entity,
+                    position: matrix.w_axis.truncate(),
+                    view_matrix: Camera::view_matrix(matrix),
+                    projection_matrix: camera.projection_matrix(aspect_ratio),
+                    depth_mode: camera.depth_mode(),
+                });
+            }
+        });
+
+        // Les lots restent dans l'ordre où leur mesh apparaît pour la première fois
+        let mut batch_indices = HashMap::new();
+
+        world.query::<(&MeshRenderer, &Transform)>(|entity, (renderer, _)| {
+            let Some(model_matrix) = world_matrix(world, entity) else {
+                return;
+            };
+
+            let batch_index = *batch_indices.entry(renderer.mesh.clone()).or_insert_with(|| {
+                view.batches.push(MeshBatch {
+                    mesh: renderer.mesh.clone(),
+                    instances: Vec::new(),
+                });
+                view.batches.len() - 1
+            });
+
+            view.batches[batch_index].instances.push(MeshInstance {
+                entity,
+                model_matrix,
+                material: renderer.material,
+                visible: true,
+            });
+        });
+
+        world.query::<(&Light, &Transform)>(|entity, (light, _)| {
+            let Some(matrix) = world_matrix(world, entity) else {
+                return;
+            };
+
+            let mut light = *light;
+            light.position = matrix.w_axis.truncate();
+            if light.direction != Vec3::ZERO {
+                light.direction = matrix.transform_vector3(light.direction).normalize();
+            }
+
+            view.lights.push((entity, light));
+        });
+
+        view
+    }
+
+    // Marque les instances hors du champ de la caméra. Elles restent dans les
+    // lots car elles peuvent encore projeter une ombre à l'écran.
+    pub fn cull(&mut self, assets: &AssetServer) -> CullStats {
+        let mut stats = CullStats::default();
+
+        let Some(camera) = self.camera else {
+            return stats;
+        };
+
+        let frustum = Frustum::from_view_proj(camera.view_proj_matrix());
+
+        for batch in &mut self.batches {
+            // Tant que le mesh charge, on ne connaît pas sa taille
+            let Some(aabb) = assets.get(&batch.mesh).map(|mesh| mesh.aabb) else {
+                continue;
+            };
+
+            for instance in &mut batch.instances {
+                instance.visible = frustum.intersects_aabb(&aabb.transform(instance.model_matrix));
+                stats.record(instance.visible);
+            }
+        }
+
+        stats
+    }
+
+    pub fn instance_count(&self) -> usize {
+        self.batches.iter().map(|batch| batch.instances.len()).sum()
+    }
+}
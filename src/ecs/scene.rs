@@ -0,0 +1,479 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use glam::{EulerRot, Quat, Vec2, Vec3};
+use ron::{extensions::Extensions, ser::PrettyConfig};
+use serde::{Deserialize, Serialize};
+
+use super::{hierarchy::Parent, render::MeshRenderer, Entity, World};
+use crate::{
+    asset::{AssetError, AssetServer, Handle},
+    camera::Camera,
+    light::{Light, LightKind},
+    material::{Material, MaterialId, MaterialLibrary},
+    mesh::{primitives, GpuMesh},
+    physics::{CollisionShape, RigidBody},
+    transform::Transform,
+};
+
+// Description d'une scène au format RON, faite pour être écrite à la main et
+// relue dans un diff. Les entités se désignent entre elles par leur nom.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneFile {
+    // Triés par nom pour que l'ordre ne change pas d'une sauvegarde à l'autre
+    pub materials: BTreeMap<String, Material>,
+    pub entities: Vec<SceneEntity>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneEntity {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    // Nom d'une autre entité du fichier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    pub transform: SceneTransform,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mesh: Option<MeshSource>,
+    // Nom d'une entrée de `materials`, le matériau par défaut sinon
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub material: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub light: Option<SceneLight>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera: Option<Camera>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rigid_body: Option<RigidBody>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collision_shape: Option<CollisionShape>,
+}
+
+// La rotation est donnée en angles d'Euler en degrés, plus lisibles qu'un
+// quaternion. Elle est appliquée dans l'ordre Y, X puis Z.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneTransform {
+    pub translation: Vec3,
+    pub rotation: Vec3,
+    pub scale: Vec3,
+}
+
+impl Default for SceneTransform {
+    fn default() -> Self {
+        Self::from(Transform::IDENTITY)
+    }
+}
+
+impl From<Transform> for SceneTransform {
+    fn from(transform: Transform) -> Self {
+        let (y, x, z) = transform.rotation.to_euler(EulerRot::YXZ);
+
+        Self {
+            translation: transform.translation,
+            // + 0.0 transforme les -0.0 en 0.0, qui n'ont rien à faire dans le fichier
+            rotation: Vec3::new(x, y, z) * (180.0 / std::f32::consts::PI) + 0.0,
+            scale: transform.scale,
+        }
+    }
+}
+
+impl From<SceneTransform> for Transform {
+    fn from(transform: SceneTransform) -> Self {
+        let rotation = transform.rotation * (std::f32::consts::PI / 180.0);
+
+        Self {
+            translation: transform.translation,
+            rotation: Quat::from_euler(EulerRot::YXZ, rotation.y, rotation.x, rotation.z),
+            scale: transform.scale,
+        }
+    }
+}
+
+// Les formes de base sont générées au chargement, les fichiers passent par
+// l'`AssetServer` et sont donc rechargés quand ils changent
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MeshSource {
+    Cube { size: Vec3 },
+    Plane { size: Vec2 },
+    Sphere { radius: f32 },
+    Cylinder { radius: f32, height: f32 },
+    Capsule { radius: f32, height: f32 },
+    // Relatif à la racine de l'`AssetServer`
+    File(PathBuf),
+}
+
+impl MeshSource {
+    fn load(&self, assets: &mut AssetServer, device: &wgpu::Device) -> Handle<GpuMesh> {
+        let mesh = match self {
+            Self::Cube { size } => primitives::cube(*size, 1),
+            Self::Plane { size } => primitives::plane(*size, 1),
+            Self::Sphere { radius } => primitives::uv_sphere(*radius, 32, 16),
+            Self::Cylinder { radius, height } => primitives::cylinder(*radius, *height, 32),
+            Self::Capsule { radius, height } => primitives::capsule(*radius, *height, 32, 8),
+            Self::File(path) => return assets.load(path),
+        };
+
+        assets.add(GpuMesh::new(&mesh, device))
+    }
+}
+
+// Sans la position, qui vient du `Transform` de l'entité. La direction est
+// dans l'espace local de l'entité.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneLight {
+    pub kind: LightKind,
+    pub color: Vec3,
+    pub intensity: f32,
+    #[serde(default = "infinite_range")]
+    pub range: f32,
+    #[serde(default = "default_direction")]
+    pub direction: Vec3,
+}
+
+impl From<Light> for SceneLight {
+    fn from(light: Light) -> Self {
+        Self {
+            kind: light.kind,
+            color: light.color,
+            intensity: light.intensity,
+            range: light.range,
+            direction: light.direction,
+        }
+    }
+}
+
+impl From<SceneLight> for Light {
+    fn from(light: SceneLight) -> Self {
+        Self {
+            kind: light.kind,
+            position: Vec3::ZERO,
+            direction: light.direction.normalize_or_zero(),
+            color: light.color,
+            intensity: light.intensity,
+            range: light.range,
+        }
+    }
+}
+
+fn infinite_range() -> f32 {
+    f32::INFINITY
+}
+
+fn default_direction() -> Vec3 {
+    Vec3::NEG_Y
+}
+
+impl SceneFile {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AssetError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    // Les références à des entités ou des matériaux inconnus sont refusées
+    // ici plutôt que d'être ignorées au moment de créer les entités
+    pub fn parse(source: &str) -> Result<Self, AssetError> {
+        let file: Self = ron_options().from_str(source)?;
+        let mut names = HashSet::new();
+
+        for entity in &file.entities {
+            if let Some(name) = &entity.name {
+                if !names.insert(name.as_str()) {
+                    return Err(format!("duplicate entity name \"{name}\"").into());
+                }
+            }
+        }
+
+        for entity in &file.entities {
+            if let Some(parent) = &entity.parent {
+                if !names.contains(parent.as_str()) {
+                    return Err(format!("unknown parent entity \"{parent}\"").into());
+                }
+            }
+
+            if let Some(material) = &entity.material {
+                if !file.materials.contains_key(material) {
+                    return Err(format!("unknown material \"{material}\"").into());
+                }
+            }
+        }
+
+        Ok(file)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), AssetError> {
+        std::fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    pub fn to_ron(&self) -> Result<String, AssetError> {
+        let config = PrettyConfig::new().indentor("    ".to_owned());
+        Ok(ron_options().to_string_pretty(self, config)? + "\n")
+    }
+
+    pub fn entity(&self, name: &str) -> Option<&SceneEntity> {
+        self.entities.iter().find(|entity| entity.name.as_deref() == Some(name))
+    }
+}
+
+// Les champs optionnels s'écrivent sans `Some(...)`
+fn ron_options() -> ron::Options {
+    ron::Options::default().with_default_extension(Extensions::IMPLICIT_SOME)
+}
+
+// Entités créées dans un monde à partir d'un `SceneFile`. Les meshes et les
+// matériaux sont gardés d'un chargement à l'autre : un matériau du même nom
+// est mis à jour sur place, le buffer de matériaux ne se remplit donc pas à
+// chaque rechargement du fichier.
+#[derive(Default)]
+pub struct SceneInstance {
+    entities: Vec<Entity>,
+    names: HashMap<String, Entity>,
+    materials: BTreeMap<String, MaterialId>,
+    meshes: Vec<(MeshSource, Handle<GpuMesh>)>,
+}
+
+impl SceneInstance {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Les entités d'un chargement précédent sont supprimées avant de créer les nouvelles
+    pub fn spawn(
+        &mut self,
+        file: &SceneFile,
+        world: &mut World,
+        assets: &mut AssetServer,
+        materials: &mut MaterialLibrary,
+        device: &wgpu::Device,
+    ) {
+        self.despawn(world);
+
+        // Les matériaux retirés du fichier ne doivent plus être sauvegardés
+        self.materials.retain(|name, _| file.materials.contains_key(name));
+
+        for (name, material) in &file.materials {
+            let existing = self.materials.get(name).and_then(|&id| materials.get_mut(id));
+
+            if let Some(existing) = existing {
+                *existing = *material;
+            } else if let Some(id) = materials.add(*material) {
+                self.materials.insert(name.clone(), id);
+            }
+        }
+
+        // Seuls les meshes encore utilisés restent chargés
+        self.meshes
+            .retain(|(source, _)| file.entities.iter().any(|entity| entity.mesh.as_ref() == Some(source)));
+
+        for description in &file.entities {
+            let entity = world.spawn();
+            world.insert(entity, Transform::from(description.transform));
+
+            if let Some(source) = &description.mesh {
+                let mesh = match self.meshes.iter().find(|(existing, _)| existing == source) {
+                    Some((_, mesh)) => mesh.clone(),
+                    None => {
+                        let mesh = source.load(assets, device);
+                        self.meshes.push((source.clone(), mesh.clone()));
+                        mesh
+                    }
+                };
+
+                // Un matériau qui n'a pas trouvé de place dans le buffer est remplacé par celui par défaut
+                let material = description
+                    .material
+                    .as_ref()
+                    .and_then(|name| self.materials.get(name).copied())
+                    .unwrap_or(materials.default_material());
+
+                world.insert(entity, MeshRenderer { mesh, material });
+            }
+
+            if let Some(light) = description.light {
+                world.insert(entity, Light::from(light));
+            }
+            if let Some(camera) = description.camera {
+                world.insert(entity, camera);
+            }
+            if let Some(rigid_body) = description.rigid_body {
+                world.insert(entity, rigid_body);
+            }
+            if let Some(collision_shape) = description.collision_shape {
+                world.insert(entity, collision_shape);
+            }
+
+            if let Some(name) = &description.name {
+                self.names.insert(name.clone(), entity);
+            }
+            self.entities.push(entity);
+        }
+
+        // Une fois toutes les entités créées, un parent peut être décrit après ses enfants
+        for (description, &entity) in file.entities.iter().zip(&self.entities) {
+            if let Some(parent) = description.parent.as_ref().and_then(|parent| self.names.get(parent)) {
+                world.insert(entity, Parent(*parent));
+            }
+        }
+    }
+
+    pub fn despawn(&mut self, world: &mut World) {
+        for entity in self.entities.drain(..) {
+            world.despawn(entity);
+        }
+
+        self.names.clear();
+    }
+
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn entity(&self, name: &str) -> Option<Entity> {
+        self.names.get(name).copied()
+    }
+
+    pub fn material(&self, name: &str) -> Option<MaterialId> {
+        self.materials.get(name).copied()
+    }
+
+    // État actuel des entités de la scène, avec ce que le code ou la physique
+    // a pu modifier depuis le chargement. Les entités ajoutées par le code
+    // n'en font pas partie.
+    pub fn to_file(&self, world: &World, materials: &MaterialLibrary) -> SceneFile {
+        let entity_names: HashMap<Entity, &String> = self.names.iter().map(|(name, &entity)| (entity, name)).collect();
+        let material_names: HashMap<MaterialId, &String> =
+            self.materials.iter().map(|(name, &id)| (id, name)).collect();
+
+        let mut file = SceneFile::default();
+
+        for &entity in &self.entities {
+            let Some(transform) = world.get::<Transform>(entity).map(|transform| *transform) else {
+                continue;
+            };
+
+            let renderer = world.get::<MeshRenderer>(entity);
+            let mesh = renderer.as_ref().and_then(|renderer| {
+                let (source, _) = self.meshes.iter().find(|(_, mesh)| *mesh == renderer.mesh)?;
+                Some(source.clone())
+            });
+            let material = renderer
+                .as_ref()
+                .and_then(|renderer| material_names.get(&renderer.material))
+                .map(|name| name.to_string());
+
+            file.entities.push(SceneEntity {
+                name: entity_names.get(&entity).map(|name| name.to_string()),
+                parent: world
+                    .get::<Parent>(entity)
+                    .and_then(|parent| entity_names.get(&parent.0))
+                    .map(|name| name.to_string()),
+                transform: transform.into(),
+                mesh,
+                material,
+                light: world.get::<Light>(entity).map(|light| SceneLight::from(*light)),
+                camera: world.get::<Camera>(entity).map(|camera| *camera),
+                rigid_body: world.get::<RigidBody>(entity).map(|rigid_body| *rigid_body),
+                collision_shape: world.get::<CollisionShape>(entity).map(|collision_shape| *collision_shape),
+            });
+        }
+
+        for (name, &id) in &self.materials {
+            if let Some(material) = materials.get(id) {
+                file.materials.insert(name.clone(), *material);
+            }
+        }
+
+        file
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+#![enable(implicit_some)]
+(
+    materials: {
+        "red": (base_color: (0.9, 0.3, 0.2)),
+        "metal": (
+            base_color: (0.2, 0.6, 0.9),
+            metallic: 0.9,
+            roughness: 0.3,
+            shading_model: MetallicRoughness,
+        ),
+    },
+    entities: [
+        (
+            name: "camera",
+            transform: (translation: (0.0, 2.0, 10.0), rotation: (-11.5, 0.0, 0.0)),
+            camera: (projection: Perspective(fov_y: 1.0471976, near: 0.1, far: 100.0)),
+        ),
+        (
+            name: "cube",
+            transform: (translation: (1.5, 0.0, -2.25), scale: (2.0, 0.2, 2.0)),
+            mesh: Cube(size: (1.0, 1.0, 1.0)),
+            material: "red",
+            rigid_body: (restitution: 0.4),
+            collision_shape: Box(half_extents: (0.5, 0.5, 0.5)),
+        ),
+        (
+            parent: "cube",
+            mesh: File("models/lamp.lmesh"),
+            material: "metal",
+            light: (kind: Spot(inner_angle: 0.3, outer_angle: 0.5), color: (1.0, 0.5, 0.1), intensity: 2.0),
+        ),
+        (
+            name: "sun",
+            light: (kind: Directional, color: (0.4, 0.5, 0.8), intensity: 0.15, direction: (-0.3, -1.0, -0.5)),
+        ),
+    ],
+)
+"#;
+
+    #[test]
+    fn ron_round_trip() {
+        let file = SceneFile::parse(SOURCE).unwrap();
+        let ron = file.to_ron().unwrap();
+        let reparsed = SceneFile::parse(&ron).unwrap();
+
+        assert_eq!(reparsed, file);
+        // Une deuxième sauvegarde ne doit rien changer au texte
+        assert_eq!(reparsed.to_ron().unwrap(), ron);
+
+        assert_eq!(file.entities.len(), 4);
+        assert_eq!(file.entities[2].parent.as_deref(), Some("cube"));
+        assert_eq!(file.entities[2].light.unwrap().range, f32::INFINITY);
+        assert_eq!(file.entities[3].transform, SceneTransform::default());
+    }
+
+    #[test]
+    fn unknown_references_are_rejected() {
+        let unknown_parent = r#"(entities: [(parent: Some("nobody"))])"#;
+        let unknown_material = r#"(entities: [(material: Some("nothing"))])"#;
+        let duplicate_name = r#"(entities: [(name: Some("a")), (name: Some("a"))])"#;
+
+        for source in [unknown_parent, unknown_material, duplicate_name] {
+            assert!(SceneFile::parse(source).is_err(), "{source}");
+        }
+    }
+
+    #[test]
+    fn transforms_convert_through_euler_degrees() {
+        let transform = Transform {
+            translation: Vec3::new(1.0, 2.0, 3.0),
+            rotation: Quat::from_euler(EulerRot::YXZ, 0.5, -0.25, 1.0),
+            scale: Vec3::new(2.0, 1.0, 0.5),
+        };
+
+        let converted = Transform::from(SceneTransform::from(transform));
+
+        assert!(converted.translation.abs_diff_eq(transform.translation, 1e-6));
+        assert!(converted.rotation.abs_diff_eq(transform.rotation, 1e-6));
+        assert!(converted.scale.abs_diff_eq(transform.scale, 1e-6));
+        assert_eq!(SceneTransform::from(Transform::IDENTITY).rotation, Vec3::ZERO);
+    }
+}
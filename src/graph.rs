@@ -0,0 +1,483 @@
+use std::{collections::HashMap, fmt};
+
+// Graphe de rendu reconstruit à chaque frame : les passes déclarent ce qu'elles
+// lisent et écrivent, le graphe en déduit l'ordre d'exécution, retire les passes
+// dont le résultat n'est jamais utilisé et alloue les textures intermédiaires.
+//
+// Chaque écriture produit une nouvelle version de la ressource, une passe qui
+// lit une version dépend donc de celle qui l'a produite, et une passe qui écrit
+// doit attendre que les lectures de la version précédente soient faites.
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ResourceId {
+    index: usize,
+    version: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TextureSize {
+    // Proportionnelle à la taille de la surface
+    Relative(f32),
+    Absolute(u32, u32),
+}
+
+#[derive(Clone, Debug)]
+pub struct TextureDesc {
+    pub label: String,
+    pub format: wgpu::TextureFormat,
+    pub size: TextureSize,
+    pub usage: wgpu::TextureUsages,
+}
+
+impl TextureDesc {
+    pub fn new(label: &str, format: wgpu::TextureFormat) -> Self {
+        Self {
+            label: label.to_owned(),
+            format,
+            size: TextureSize::Relative(1.0),
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        }
+    }
+
+    pub fn with_size(mut self, size: TextureSize) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn with_usage(mut self, usage: wgpu::TextureUsages) -> Self {
+        self.usage |= usage;
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RenderGraphError {
+    Cycle { passes: Vec<String> },
+    StaleWrite { pass: String, resource: String },
+}
+
+impl fmt::Display for RenderGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Cycle { passes } => write!(f, "render graph has a cycle between passes {passes:?}"),
+            Self::StaleWrite { pass, resource } => write!(
+                f,
+                "pass \"{pass}\" writes an outdated version of \"{resource}\""
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RenderGraphError {}
+
+enum ResourceKind<'a> {
+    Transient(TextureDesc),
+    Texture(&'a wgpu::TextureView),
+    Buffer(&'a wgpu::Buffer),
+    // Gérée ailleurs (ex: la shadow map), ne sert qu'à ordonner les passes
+    External,
+}
+
+struct ResourceNode<'a> {
+    label: String,
+    kind: ResourceKind<'a>,
+    version: u32,
+}
+
+type PassFn<'a> = Box<dyn FnOnce(&mut PassContext) + 'a>;
+
+struct PassNode<'a> {
+    name: String,
+    reads: Vec<ResourceId>,
+    writes: Vec<ResourceId>,
+    run: PassFn<'a>,
+}
+
+pub struct PassBuilder<'g, 'a> {
+    name: &'g str,
+    resources: &'g mut Vec<ResourceNode<'a>>,
+    reads: Vec<ResourceId>,
+    writes: Vec<ResourceId>,
+    errors: &'g mut Vec<RenderGraphError>,
+}
+
+impl PassBuilder<'_, '_> {
+    pub fn read(&mut self, id: ResourceId) -> ResourceId {
+        self.reads.push(id);
+        id
+    }
+
+    // Renvoie la nouvelle version de la ressource, à utiliser par les passes suivantes
+    pub fn write(&mut self, id: ResourceId) -> ResourceId {
+        let resource = &mut self.resources[id.index];
+
+        if resource.version != id.version {
+            self.errors.push(RenderGraphError::StaleWrite {
+                pass: self.name.to_owned(),
+                resource: resource.label.clone(),
+            });
+        }
+
+        resource.version += 1;
+
+        let new_id = ResourceId {
+            index: id.index,
+            version: resource.version,
+        };
+
+        self.writes.push(new_id);
+        new_id
+    }
+}
+
+pub struct PassContext<'r> {
+    pub device: &'r wgpu::Device,
+    pub encoder: &'r mut wgpu::CommandEncoder,
+    textures: &'r [Option<&'r wgpu::TextureView>],
+    buffers: &'r [Option<&'r wgpu::Buffer>],
+}
+
+// N'importe quelle version d'une ressource désigne la même texture ou le même buffer
+impl<'r> PassContext<'r> {
+    pub fn texture(&self, id: ResourceId) -> &'r wgpu::TextureView {
+        self.textures[id.index].expect("resource is not a texture")
+    }
+
+    pub fn buffer(&self, id: ResourceId) -> &'r wgpu::Buffer {
+        self.buffers[id.index].expect("resource is not a buffer")
+    }
+}
+
+#[derive(Default)]
+pub struct RenderGraph<'a> {
+    resources: Vec<ResourceNode<'a>>,
+    passes: Vec<PassNode<'a>>,
+    errors: Vec<RenderGraphError>,
+}
+
+impl<'a> RenderGraph<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add_resource(&mut self, label: &str, kind: ResourceKind<'a>) -> ResourceId {
+        self.resources.push(ResourceNode {
+            label: label.to_owned(),
+            kind,
+            version: 0,
+        });
+
+        ResourceId {
+            index: self.resources.len() - 1,
+            version: 0,
+        }
+    }
+
+    pub fn create_texture(&mut self, desc: TextureDesc) -> ResourceId {
+        let label = desc.label.clone();
+        self.add_resource(&label, ResourceKind::Transient(desc))
+    }
+
+    pub fn import_texture(&mut self, label: &str, view: &'a wgpu::TextureView) -> ResourceId {
+        self.add_resource(label, ResourceKind::Texture(view))
+    }
+
+    pub fn import_buffer(&mut self, label: &str, buffer: &'a wgpu::Buffer) -> ResourceId {
+        self.add_resource(label, ResourceKind::Buffer(buffer))
+    }
+
+    pub fn import_external(&mut self, label: &str) -> ResourceId {
+        self.add_resource(label, ResourceKind::External)
+    }
+
+    pub fn add_pass<R>(
+        &mut self,
+        name: &str,
+        setup: impl FnOnce(&mut PassBuilder) -> R,
+        run: impl FnOnce(&mut PassContext) + 'a,
+    ) -> R {
+        let mut builder = PassBuilder {
+            name,
+            resources: &mut self.resources,
+            reads: Vec::new(),
+            writes: Vec::new(),
+            errors: &mut self.errors,
+        };
+
+        let result = setup(&mut builder);
+        let reads = builder.reads;
+        let writes = builder.writes;
+
+        self.passes.push(PassNode {
+            name: name.to_owned(),
+            reads,
+            writes,
+            run: Box::new(run),
+        });
+
+        result
+    }
+
+    // Ordre d'exécution des passes conservées
+    pub fn compile(&self) -> Result<Vec<usize>, RenderGraphError> {
+        if let Some(error) = self.errors.first() {
+            return Err(error.clone());
+        }
+
+        let mut producers = HashMap::new();
+        let mut readers: HashMap<ResourceId, Vec<usize>> = HashMap::new();
+
+        for (pass_index, pass) in self.passes.iter().enumerate() {
+            for &id in &pass.writes {
+                producers.insert(id, pass_index);
+            }
+
+            for &id in &pass.reads {
+                readers.entry(id).or_default().push(pass_index);
+            }
+        }
+
+        let mut dependencies = vec![Vec::new(); self.passes.len()];
+
+        for (pass_index, pass) in self.passes.iter().enumerate() {
+            for id in &pass.reads {
+                if let Some(&producer) = producers.get(id) {
+                    dependencies[pass_index].push(producer);
+                }
+            }
+
+            for id in &pass.writes {
+                let previous = ResourceId {
+                    index: id.index,
+                    version: id.version - 1,
+                };
+
+                if let Some(&producer) = producers.get(&previous) {
+                    dependencies[pass_index].push(producer);
+                }
+
+                for &reader in readers.get(&previous).into_iter().flatten() {
+                    if reader != pass_index {
+                        dependencies[pass_index].push(reader);
+                    }
+                }
+            }
+        }
+
+        // On garde les passes qui écrivent une ressource importée (ou qui n'écrivent
+        // rien, leurs effets sont ailleurs) et tout ce dont elles dépendent
+        let mut kept = vec![false; self.passes.len()];
+        let mut stack: Vec<usize> = self
+            .passes
+            .iter()
+            .enumerate()
+            .filter(|(_, pass)| {
+                pass.writes.is_empty()
+                    || pass
+                        .writes
+                        .iter()
+                        .any(|id| !matches!(self.resources[id.index].kind, ResourceKind::Transient(_)))
+            })
+            .map(|(pass_index, _)| pass_index)
+            .collect();
+
+        while let Some(pass_index) = stack.pop() {
+            if !kept[pass_index] {
+                kept[pass_index] = true;
+                stack.extend(&dependencies[pass_index]);
+            }
+        }
+
+        // Tri topologique, à égalité on respecte l'ordre de déclaration
+        let mut remaining: Vec<usize> = dependencies
+            .iter()
+            .enumerate()
+            .map(|(pass_index, deps)| deps.iter().filter(|&&dep| kept[dep] && dep != pass_index).count())
+            .collect();
+
+        let mut order = Vec::new();
+        let mut done = vec![false; self.passes.len()];
+
+        while order.len() < kept.iter().filter(|&&k| k).count() {
+            let next = (0..self.passes.len()).find(|&pass_index| kept[pass_index] && !done[pass_index] && remaining[pass_index] == 0);
+
+            let Some(next) = next else {
+                return Err(RenderGraphError::Cycle {
+                    passes: (0..self.passes.len())
+                        .filter(|&pass_index| kept[pass_index] && !done[pass_index])
+                        .map(|pass_index| self.passes[pass_index].name.clone())
+                        .collect(),
+                });
+            };
+
+            done[next] = true;
+            order.push(next);
+
+            for (pass_index, deps) in dependencies.iter().enumerate() {
+                remaining[pass_index] -= deps.iter().filter(|&&dep| dep == next && dep != pass_index).count();
+            }
+        }
+
+        Ok(order)
+    }
+
+    pub fn execute(
+        self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        pool: &mut TransientPool,
+    ) -> Result<(), RenderGraphError> {
+        let order = self.compile()?;
+
+        pool.begin_frame();
+
+        let mut transient_slots = vec![None; self.resources.len()];
+
+        for &pass_index in &order {
+            let pass = &self.passes[pass_index];
+
+            for id in pass.reads.iter().chain(&pass.writes) {
+                if let ResourceKind::Transient(desc) = &self.resources[id.index].kind {
+                    if transient_slots[id.index].is_none() {
+                        transient_slots[id.index] = Some(pool.acquire(device, desc));
+                    }
+                }
+            }
+        }
+
+        let textures: Vec<Option<&wgpu::TextureView>> = self
+            .resources
+            .iter()
+            .zip(&transient_slots)
+            .map(|(resource, slot)| match resource.kind {
+                ResourceKind::Transient(_) => slot.map(|slot| pool.view(slot)),
+                ResourceKind::Texture(view) => Some(view),
+                _ => None,
+            })
+            .collect();
+
+        let buffers: Vec<Option<&wgpu::Buffer>> = self
+            .resources
+            .iter()
+            .map(|resource| match resource.kind {
+                ResourceKind::Buffer(buffer) => Some(buffer),
+                _ => None,
+            })
+            .collect();
+
+        let mut passes: Vec<Option<PassNode>> = self.passes.into_iter().map(Some).collect();
+
+        for pass_index in order {
+            let pass = passes[pass_index].take().unwrap();
+
+            let mut ctx = PassContext {
+                device,
+                encoder: &mut *encoder,
+                textures: &textures,
+                buffers: &buffers,
+            };
+
+            (pass.run)(&mut ctx);
+        }
+
+        Ok(())
+    }
+}
+
+struct PoolEntry {
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    usage: wgpu::TextureUsages,
+    view: wgpu::TextureView,
+    last_used: u64,
+}
+
+// Garde les textures intermédiaires d'une frame à l'autre. Celles qui ne servent
+// plus (après un redimensionnement par exemple) sont libérées à la frame suivante.
+pub struct TransientPool {
+    width: u32,
+    height: u32,
+    entries: Vec<PoolEntry>,
+    frame: u64,
+}
+
+impl TransientPool {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            entries: Vec::new(),
+            frame: 0,
+        }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn begin_frame(&mut self) {
+        self.frame += 1;
+        let frame = self.frame;
+        self.entries.retain(|entry| entry.last_used + 1 >= frame);
+    }
+
+    fn acquire(&mut self, device: &wgpu::Device, desc: &TextureDesc) -> usize {
+        let (width, height) = match desc.size {
+            TextureSize::Relative(scale) => (
+                ((self.width as f32 * scale) as u32).max(1),
+                ((self.height as f32 * scale) as u32).max(1),
+            ),
+            TextureSize::Absolute(width, height) => (width.max(1), height.max(1)),
+        };
+
+        let frame = self.frame;
+        let free_entry = self.entries.iter().position(|entry| {
+            entry.last_used != frame
+                && entry.format == desc.format
+                && entry.width == width
+                && entry.height == height
+                && entry.usage == desc.usage
+        });
+
+        if let Some(index) = free_entry {
+            self.entries[index].last_used = frame;
+            return index;
+        }
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(&desc.label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: desc.format,
+            usage: desc.usage,
+            view_formats: &[],
+        });
+
+        self.entries.push(PoolEntry {
+            format: desc.format,
+            width,
+            height,
+            usage: desc.usage,
+            view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            last_used: frame,
+        });
+
+        self.entries.len() - 1
+    }
+
+    fn view(&self, index: usize) -> &wgpu::TextureView {
+        &self.entries[index].view
+    }
+}
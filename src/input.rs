@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+
+use glam::Vec2;
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent};
+
+// Hauteur d'une ligne de molette en pixels, pour ramener les deux types de
+// défilement à la même unité
+const PIXELS_PER_LINE: f32 = 20.0;
+
+// État du clavier et de la souris, alimenté par les événements de la fenêtre.
+// `end_frame` doit être appelé à la fin de chaque frame pour remettre à zéro
+// les déplacements et les appuis de la frame.
+#[derive(Clone, Debug, Default)]
+pub struct Input {
+    keys_down: HashSet<VirtualKeyCode>,
+    keys_pressed: HashSet<VirtualKeyCode>,
+    buttons_down: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    cursor_position: Option<Vec2>,
+    cursor_delta: Vec2,
+    // En lignes, positif vers le haut
+    scroll_delta: f32,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput { input, .. } => {
+                let Some(key) = input.virtual_keycode else {
+                    return;
+                };
+
+                match input.state {
+                    ElementState::Pressed => {
+                        // La répétition du clavier renvoie Pressed tant que la touche est enfoncée
+                        if self.keys_down.insert(key) {
+                            self.keys_pressed.insert(key);
+                        }
+                    }
+                    ElementState::Released => {
+                        self.keys_down.remove(&key);
+                    }
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => {
+                    if self.buttons_down.insert(*button) {
+                        self.buttons_pressed.insert(*button);
+                    }
+                }
+                ElementState::Released => {
+                    self.buttons_down.remove(button);
+                }
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = Vec2::new(position.x as f32, position.y as f32);
+
+                if let Some(previous) = self.cursor_position {
+                    self.cursor_delta += position - previous;
+                }
+                self.cursor_position = Some(position);
+            }
+            WindowEvent::CursorLeft { .. } => self.cursor_position = None,
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.scroll_delta += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_LINE,
+                };
+            }
+            // Les touches relâchées hors de la fenêtre ne seraient jamais vues
+            WindowEvent::Focused(false) => {
+                self.keys_down.clear();
+                self.buttons_down.clear();
+            }
+            _ => {}
+        }
+    }
+
+    pub fn end_frame(&mut self) {
+        self.keys_pressed.clear();
+        self.buttons_pressed.clear();
+        self.cursor_delta = Vec2::ZERO;
+        self.scroll_delta = 0.0;
+    }
+
+    pub fn key_down(&self, key: VirtualKeyCode) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    // Seulement pendant la frame où la touche a été enfoncée
+    pub fn key_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    pub fn button_down(&self, button: MouseButton) -> bool {
+        self.buttons_down.contains(&button)
+    }
+
+    pub fn button_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn cursor_position(&self) -> Option<Vec2> {
+        self.cursor_position
+    }
+
+    // En pixels depuis la frame précédente, Y vers le bas
+    pub fn cursor_delta(&self) -> Vec2 {
+        self.cursor_delta
+    }
+
+    pub fn scroll_delta(&self) -> f32 {
+        self.scroll_delta
+    }
+
+    // -1, 0 ou 1 selon les deux touches enfoncées
+    pub fn axis(&self, negative: VirtualKeyCode, positive: VirtualKeyCode) -> f32 {
+        self.key_down(positive) as i32 as f32 - self.key_down(negative) as i32 as f32
+    }
+}
@@ -1,8 +1,31 @@
-use winit::window::Window;
+use winit::{event::WindowEvent, window::Window};
+
+pub mod anim;
+pub mod asset;
+pub mod bounds;
+pub mod buffer;
+pub mod bvh;
+pub mod camera;
+pub mod culling;
+pub mod ecs;
+pub mod graph;
+pub mod input;
+pub mod light;
+pub mod material;
+pub mod mesh;
+pub mod particles;
+pub mod physics;
+pub mod post;
+pub mod ray;
+pub mod shadow;
+pub mod skin;
+pub mod texture;
+pub mod transform;
 
 #[allow(unused)]
 pub trait App {
     fn new(window: &Window) -> Self;
     fn update(&mut self) {}
     fn on_resize(&mut self, width: u32, height: u32) {}
+    fn on_window_event(&mut self, event: &WindowEvent) {}
 }
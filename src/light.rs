@@ -0,0 +1,261 @@
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+// Un tableau uniforme plutôt qu'un storage buffer : WebGL2 n'a pas de storage
+// buffers et on garde le même shader pour toutes les cibles.
+pub const MAX_LIGHTS: usize = 32;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LightKind {
+    Point,
+    Directional,
+    Spot { inner_angle: f32, outer_angle: f32 },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Light {
+    pub kind: LightKind,
+    pub position: Vec3,
+    pub direction: Vec3,
+    pub color: Vec3,
+    pub intensity: f32,
+    pub range: f32,
+}
+
+impl Light {
+    pub fn point(position: Vec3, color: Vec3, intensity: f32, range: f32) -> Self {
+        Self {
+            kind: LightKind::Point,
+            position,
+            direction: Vec3::NEG_Y,
+            color,
+            intensity,
+            range,
+        }
+    }
+
+    pub fn directional(direction: Vec3, color: Vec3, intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Directional,
+            position: Vec3::ZERO,
+            direction: direction.normalize(),
+            color,
+            intensity,
+            range: f32::INFINITY,
+        }
+    }
+
+    pub fn spot(
+        position: Vec3,
+        direction: Vec3,
+        color: Vec3,
+        intensity: f32,
+        range: f32,
+        inner_angle: f32,
+        outer_angle: f32,
+    ) -> Self {
+        Self {
+            kind: LightKind::Spot {
+                inner_angle,
+                outer_angle,
+            },
+            position,
+            direction: direction.normalize(),
+            color,
+            intensity,
+            range,
+        }
+    }
+
+    fn to_gpu(self) -> GpuLight {
+        let (kind, spot_inner_cos, spot_outer_cos) = match self.kind {
+            LightKind::Point => (0, 0.0, 0.0),
+            LightKind::Directional => (1, 0.0, 0.0),
+            LightKind::Spot {
+                inner_angle,
+                outer_angle,
+            } => {
+                // Le shader divise par la différence des deux cosinus, des
+                // angles égaux donnent un bord net plutôt qu'une division par zéro
+                let outer_cos = outer_angle.cos();
+                (2, inner_angle.cos().max(outer_cos + 1e-4), outer_cos)
+            }
+        };
+
+        GpuLight {
+            pos: self.position.into(),
+            range: self.range.min(f32::MAX),
+            direction: self.direction.into(),
+            kind,
+            color: self.color.into(),
+            intensity: self.intensity,
+            spot_inner_cos,
+            spot_outer_cos,
+            _pad: [0; 2],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LightId(u32);
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct GpuLight {
+    pos: [f32; 3],
+    range: f32,
+    direction: [f32; 3],
+    kind: u32,
+    color: [f32; 3],
+    intensity: f32,
+    spot_inner_cos: f32,
+    spot_outer_cos: f32,
+    _pad: [u32; 2],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct LightDataUniform {
+    count: u32,
+    _pad: [u32; 3],
+    lights: [GpuLight; MAX_LIGHTS],
+}
+
+pub struct LightList {
+    lights: Vec<(LightId, Light)>,
+    next_id: u32,
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+}
+
+impl LightList {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Light Buffer"),
+            size: std::mem::size_of::<LightDataUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Light Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Light Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            lights: Vec::new(),
+            next_id: 0,
+            buffer,
+            bind_group_layout,
+            bind_group,
+        }
+    }
+
+    // Renvoie None si la liste est déjà pleine
+    pub fn add(&mut self, light: Light) -> Option<LightId> {
+        if self.lights.len() >= MAX_LIGHTS {
+            return None;
+        }
+
+        let id = LightId(self.next_id);
+        self.next_id += 1;
+        self.lights.push((id, light));
+
+        Some(id)
+    }
+
+    pub fn remove(&mut self, id: LightId) -> Option<Light> {
+        let index = self.index_of(id)?;
+        Some(self.lights.remove(index).1)
+    }
+
+    pub fn clear(&mut self) {
+        self.lights.clear();
+    }
+
+    pub fn get(&self, id: LightId) -> Option<&Light> {
+        self.lights.iter().find(|(light_id, _)| *light_id == id).map(|(_, light)| light)
+    }
+
+    pub fn get_mut(&mut self, id: LightId) -> Option<&mut Light> {
+        self.lights.iter_mut().find(|(light_id, _)| *light_id == id).map(|(_, light)| light)
+    }
+
+    // Position de la lumière dans le tableau envoyé au shader
+    pub fn index_of(&self, id: LightId) -> Option<usize> {
+        self.lights.iter().position(|(light_id, _)| *light_id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (LightId, &Light)> {
+        self.lights.iter().map(|(id, light)| (*id, light))
+    }
+
+    pub fn len(&self) -> usize {
+        self.lights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    pub fn upload(&self, queue: &wgpu::Queue) {
+        let mut light_data = LightDataUniform {
+            count: self.lights.len() as u32,
+            _pad: [0; 3],
+            lights: [bytemuck::Zeroable::zeroed(); MAX_LIGHTS],
+        };
+
+        for (gpu_light, (_, light)) in light_data.lights.iter_mut().zip(&self.lights) {
+            *gpu_light = light.to_gpu();
+        }
+
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&light_data));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spot_cones_never_have_a_zero_falloff() {
+        for (inner_angle, outer_angle) in [(0.3, 0.5), (0.5, 0.5), (0.6, 0.5)] {
+            let light = Light::spot(Vec3::ZERO, Vec3::NEG_Y, Vec3::ONE, 1.0, 10.0, inner_angle, outer_angle);
+            let gpu = light.to_gpu();
+
+            assert!(gpu.spot_inner_cos > gpu.spot_outer_cos, "{inner_angle} {outer_angle}");
+            assert_eq!(gpu.spot_outer_cos, outer_angle.cos());
+        }
+
+        // Un cône normal n'est pas modifié
+        let light = Light::spot(Vec3::ZERO, Vec3::NEG_Y, Vec3::ONE, 1.0, 10.0, 0.3, 0.5);
+        assert_eq!(light.to_gpu().spot_inner_cos, 0.3f32.cos());
+    }
+}
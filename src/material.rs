@@ -0,0 +1,277 @@
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::texture::{ColorSpace, Texture};
+
+// Comme pour les lumières, un tableau uniforme pour rester compatible WebGL2
+pub const MAX_MATERIALS: usize = 64;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShadingModel {
+    BlinnPhong,
+    MetallicRoughness,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Material {
+    pub base_color: Vec3,
+    pub specular_strength: f32,
+    pub shininess: f32,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub shading_model: ShadingModel,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::phong(Vec3::ONE, 1.0, 256.0)
+    }
+}
+
+impl Material {
+    pub fn phong(base_color: Vec3, specular_strength: f32, shininess: f32) -> Self {
+        Self {
+            base_color,
+            specular_strength,
+            shininess,
+            metallic: 0.0,
+            roughness: 0.5,
+            shading_model: ShadingModel::BlinnPhong,
+        }
+    }
+
+    pub fn pbr(base_color: Vec3, metallic: f32, roughness: f32) -> Self {
+        Self {
+            base_color,
+            specular_strength: 1.0,
+            shininess: 256.0,
+            metallic,
+            roughness,
+            shading_model: ShadingModel::MetallicRoughness,
+        }
+    }
+
+    fn to_gpu(self) -> GpuMaterial {
+        GpuMaterial {
+            base_color: self.base_color.into(),
+            specular_strength: self.specular_strength,
+            shininess: self.shininess,
+            metallic: self.metallic,
+            roughness: self.roughness.clamp(0.045, 1.0),
+            shading_model: match self.shading_model {
+                ShadingModel::BlinnPhong => 0,
+                ShadingModel::MetallicRoughness => 1,
+            },
+        }
+    }
+}
+
+// Index du matériau dans le buffer, c'est ce qui est stocké dans chaque instance
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MaterialId(u32);
+
+impl MaterialId {
+    pub fn index(self) -> u32 {
+        self.0
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct GpuMaterial {
+    base_color: [f32; 3],
+    specular_strength: f32,
+    shininess: f32,
+    metallic: f32,
+    roughness: f32,
+    shading_model: u32,
+}
+
+pub struct MaterialLibrary {
+    materials: Vec<Material>,
+    dirty: bool,
+    buffer: wgpu::Buffer,
+    default_albedo: Texture,
+    default_normal: Texture,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+}
+
+impl MaterialLibrary {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Material Buffer"),
+            size: (MAX_MATERIALS * std::mem::size_of::<GpuMaterial>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Material Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_layout_entry(1),
+                sampler_layout_entry(2),
+                texture_layout_entry(3),
+                sampler_layout_entry(4),
+            ],
+        });
+
+        // Blanc et normale plate, ne changent rien au rendu des matériaux sans texture
+        let default_albedo = Texture::solid(device, queue, "Default Albedo", [255, 255, 255, 255], ColorSpace::Srgb);
+        let default_normal = Texture::solid(device, queue, "Default Normal", [128, 128, 255, 255], ColorSpace::Linear);
+
+        let bind_group = create_bind_group(device, &bind_group_layout, &buffer, &default_albedo, &default_normal);
+
+        let mut library = Self {
+            materials: Vec::new(),
+            dirty: true,
+            buffer,
+            default_albedo,
+            default_normal,
+            bind_group_layout,
+            bind_group,
+        };
+
+        // Le matériau 0 sert pour les instances qui n'en précisent pas
+        library.add(Material::default());
+        library
+    }
+
+    pub fn default_material(&self) -> MaterialId {
+        MaterialId(0)
+    }
+
+    // Renvoie None si le buffer est déjà plein
+    pub fn add(&mut self, material: Material) -> Option<MaterialId> {
+        if self.materials.len() >= MAX_MATERIALS {
+            return None;
+        }
+
+        self.materials.push(material);
+        self.dirty = true;
+
+        Some(MaterialId(self.materials.len() as u32 - 1))
+    }
+
+    pub fn get(&self, id: MaterialId) -> Option<&Material> {
+        self.materials.get(id.0 as usize)
+    }
+
+    pub fn get_mut(&mut self, id: MaterialId) -> Option<&mut Material> {
+        let material = self.materials.get_mut(id.0 as usize)?;
+        self.dirty = true;
+        Some(material)
+    }
+
+    pub fn len(&self) -> usize {
+        self.materials.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.materials.is_empty()
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    // Bind group avec les textures par défaut
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    // Les textures sont communes à tout un draw call, contrairement aux
+    // paramètres qui sont indexés par instance. `None` garde la texture par défaut.
+    pub fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        albedo: Option<&Texture>,
+        normal: Option<&Texture>,
+    ) -> wgpu::BindGroup {
+        create_bind_group(
+            device,
+            &self.bind_group_layout,
+            &self.buffer,
+            albedo.unwrap_or(&self.default_albedo),
+            normal.unwrap_or(&self.default_normal),
+        )
+    }
+
+    pub fn upload(&mut self, queue: &wgpu::Queue) {
+        if !self.dirty {
+            return;
+        }
+
+        let gpu_materials: Vec<GpuMaterial> = self.materials.iter().map(|material| material.to_gpu()).collect();
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&gpu_materials));
+        self.dirty = false;
+    }
+}
+
+fn texture_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        },
+        count: None,
+    }
+}
+
+fn sampler_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+        count: None,
+    }
+}
+
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    buffer: &wgpu::Buffer,
+    albedo: &Texture,
+    normal: &Texture,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Material Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(&albedo.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::Sampler(&albedo.sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(&normal.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 4,
+                resource: wgpu::BindingResource::Sampler(&normal.sampler),
+            },
+        ],
+    })
+}
@@ -0,0 +1,269 @@
+use std::{ops::Range, path::Path};
+
+use glam::Vec3;
+use wgpu::util::DeviceExt;
+
+use crate::{
+    asset::{self, AssetError},
+    bounds::Aabb,
+};
+
+pub mod binary;
+pub mod gltf;
+pub mod obj;
+pub mod primitives;
+mod processing;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct VertexData {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+    // w vaut 1 ou -1 selon le sens de la bitangente
+    pub tangent: [f32; 4],
+}
+
+impl VertexData {
+    pub fn new(position: [f32; 3], normal: [f32; 3]) -> Self {
+        Self {
+            position,
+            normal,
+            uv: [0.0, 0.0],
+            tangent: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<VertexData>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    pub vertices: Vec<VertexData>,
+    pub indices: Vec<u32>,
+}
+
+// Portion d'un mesh dessinée avec son propre matériau. Les indices sont
+// relatifs à `base_vertex`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SubMesh {
+    pub index_start: u32,
+    pub index_count: u32,
+    pub base_vertex: i32,
+    // Indice du matériau dans le modèle d'origine, à faire correspondre par l'appelant
+    pub material: u32,
+}
+
+impl SubMesh {
+    pub fn whole(mesh: &Mesh) -> Self {
+        Self {
+            index_start: 0,
+            index_count: mesh.indices.len() as u32,
+            base_vertex: 0,
+            material: 0,
+        }
+    }
+
+    pub fn index_range(&self) -> Range<u32> {
+        self.index_start..self.index_start + self.index_count
+    }
+}
+
+impl Mesh {
+    // Charge un .obj, .gltf, .glb ou .lmesh en fusionnant tous ses meshes, avec
+    // les transformations des noeuds non skinnés appliquées pour le glTF
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AssetError> {
+        Ok(Self::load_sub_meshes(path)?.0)
+    }
+
+    // Comme `load`, mais chaque primitive garde sa plage d'indices et son matériau
+    pub fn load_sub_meshes(path: impl AsRef<Path>) -> Result<(Self, Vec<SubMesh>), AssetError> {
+        let path = path.as_ref();
+        let mut mesh = Mesh::default();
+        let mut sub_meshes = Vec::new();
+
+        match asset::extension(path).as_str() {
+            "lmesh" => {
+                let file = binary::MeshFile::load(path)?;
+                let view = file.view();
+                return Ok((view.to_mesh(), view.sub_meshes.to_vec()));
+            }
+            "obj" => {
+                for obj_mesh in obj::ObjModel::load(path)?.meshes {
+                    let material = obj_mesh.material.unwrap_or(0) as u32;
+                    sub_meshes.push(mesh.append_sub_mesh(&obj_mesh.mesh, material));
+                }
+            }
+            "gltf" | "glb" => {
+                let model = gltf::GltfModel::load(path)?;
+
+                for (node, transform) in model.nodes.iter().zip(model.world_transforms()) {
+                    let Some(mesh_index) = node.mesh else {
+                        continue;
+                    };
+
+                    for primitive in &model.meshes[mesh_index].primitives {
+                        let mut primitive_mesh = primitive.mesh.clone();
+
+                        // La spécification glTF ignore la transformation des noeuds skinnés,
+                        // ce sont les matrices des os qui placent le mesh
+                        if node.skin.is_none() {
+                            primitive_mesh.transform(transform);
+                        }
+
+                        let material = primitive.material.unwrap_or(0) as u32;
+                        sub_meshes.push(mesh.append_sub_mesh(&primitive_mesh, material));
+                    }
+                }
+            }
+            extension => return Err(format!("unsupported mesh format \"{extension}\"").into()),
+        }
+
+        Ok((mesh, sub_meshes))
+    }
+}
+
+// Le format 16 bits est choisi dès que tous les sommets sont adressables, la
+// valeur 0xFFFF étant réservée au redémarrage de primitive
+pub fn fits_u16_indices(vertex_count: usize) -> bool {
+    vertex_count <= u16::MAX as usize
+}
+
+pub struct GpuMesh {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub index_format: wgpu::IndexFormat,
+    pub index_count: u32,
+    pub sub_meshes: Vec<SubMesh>,
+    // Boîte englobante dans l'espace du mesh, pour le culling
+    pub aabb: Aabb,
+}
+
+impl GpuMesh {
+    pub fn new(mesh: &Mesh, device: &wgpu::Device) -> Self {
+        Self::with_sub_meshes(mesh, &[SubMesh::whole(mesh)], device)
+    }
+
+    pub fn with_sub_meshes(mesh: &Mesh, sub_meshes: &[SubMesh], device: &wgpu::Device) -> Self {
+        if fits_u16_indices(mesh.vertices.len()) {
+            let indices: Vec<u16> = mesh.indices.iter().map(|&index| index as u16).collect();
+            Self::from_parts(&mesh.vertices, Indices::U16(&indices), sub_meshes, device)
+        } else {
+            Self::from_parts(&mesh.vertices, Indices::U32(&mesh.indices), sub_meshes, device)
+        }
+    }
+
+    // Les données d'un .lmesh partent directement dans les buffers
+    pub fn from_view(view: &binary::MeshView, device: &wgpu::Device) -> Self {
+        Self::from_parts(view.vertices, view.indices, view.sub_meshes, device)
+    }
+
+    pub fn from_parts(vertices: &[VertexData], indices: Indices, sub_meshes: &[SubMesh], device: &wgpu::Device) -> Self {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("VertexData Buffer"),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Index Buffer"),
+            contents: indices.as_bytes(),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self {
+            vertex_buffer,
+            index_buffer,
+            index_format: indices.format(),
+            index_count: indices.len() as u32,
+            sub_meshes: sub_meshes.to_vec(),
+            aabb: Aabb::from_points(vertices.iter().map(|vertex| Vec3::from(vertex.position))),
+        }
+    }
+
+    // Les sommets vont dans le slot 0, les instances restent à la charge de l'appelant
+    pub fn set_buffers<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), self.index_format);
+    }
+
+    // WebGL2 ne supporte pas de `base_vertex` non nul, les sous-meshes créés
+    // par `Mesh::append_sub_mesh` ont donc leurs indices déjà décalés
+    pub fn draw_sub_mesh(&self, render_pass: &mut wgpu::RenderPass, sub_mesh: &SubMesh, instances: Range<u32>) {
+        render_pass.draw_indexed(sub_mesh.index_range(), sub_mesh.base_vertex, instances);
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass, instances: Range<u32>) {
+        for sub_mesh in &self.sub_meshes {
+            self.draw_sub_mesh(render_pass, sub_mesh, instances.clone());
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Indices<'a> {
+    U16(&'a [u16]),
+    U32(&'a [u32]),
+}
+
+impl Indices<'_> {
+    pub fn len(&self) -> usize {
+        match self {
+            Self::U16(indices) => indices.len(),
+            Self::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn format(&self) -> wgpu::IndexFormat {
+        match self {
+            Self::U16(_) => wgpu::IndexFormat::Uint16,
+            Self::U32(_) => wgpu::IndexFormat::Uint32,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::U16(indices) => bytemuck::cast_slice(indices),
+            Self::U32(indices) => bytemuck::cast_slice(indices),
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<u32> {
+        match self {
+            Self::U16(indices) => indices.iter().map(|&index| index as u32).collect(),
+            Self::U32(indices) => indices.to_vec(),
+        }
+    }
+}
@@ -0,0 +1,484 @@
+use std::{
+    fmt, fs,
+    io::{self, Read},
+    path::Path,
+};
+
+use glam::Vec3;
+
+use super::{fits_u16_indices, Indices, Mesh, SubMesh, VertexData};
+use crate::bounds::Aabb;
+
+// Format .lmesh : un en-tête suivi des sommets, des indices (complétés à un
+// multiple de 4 octets) puis des sous-meshes, exactement comme en mémoire
+// (little endian), pour pouvoir les envoyer tels quels au GPU
+pub const MAGIC: [u8; 4] = *b"LMSH";
+pub const VERSION: u32 = 2;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Header {
+    pub magic: [u8; 4],
+    pub version: u32,
+    // Taille de `VertexData` à l'écriture, pour refuser un fichier d'une autre disposition
+    pub vertex_stride: u32,
+    pub vertex_count: u32,
+    pub index_count: u32,
+    // 2 ou 4 octets
+    pub index_size: u32,
+    pub sub_mesh_count: u32,
+    pub reserved: u32,
+    pub aabb_min: [f32; 3],
+    pub aabb_max: [f32; 3],
+}
+
+#[derive(Debug)]
+pub enum BinaryMeshError {
+    Io(io::Error),
+    BadMagic,
+    UnsupportedVersion(u32),
+    VertexStrideMismatch(u32),
+    InvalidIndexSize(u32),
+    Truncated,
+    Misaligned,
+    // Plage d'indices du sous-mesh hors du buffer d'indices
+    SubMeshOutOfBounds(usize),
+    // Indice qui, une fois décalé par `base_vertex`, sort du buffer de sommets
+    IndexOutOfBounds { sub_mesh: usize, index: i64 },
+}
+
+impl fmt::Display for BinaryMeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{error}"),
+            Self::BadMagic => write!(f, "not a lux mesh file"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported mesh version {version} (expected {VERSION})"),
+            Self::VertexStrideMismatch(stride) => write!(
+                f,
+                "vertex stride is {stride} bytes, expected {}",
+                std::mem::size_of::<VertexData>()
+            ),
+            Self::InvalidIndexSize(size) => write!(f, "invalid index size {size}"),
+            Self::Truncated => write!(f, "mesh file is truncated"),
+            Self::Misaligned => write!(f, "mesh data is not aligned on 4 bytes"),
+            Self::SubMeshOutOfBounds(sub_mesh) => write!(f, "sub-mesh {sub_mesh} is out of the index buffer"),
+            Self::IndexOutOfBounds { sub_mesh, index } => {
+                write!(f, "sub-mesh {sub_mesh} references vertex {index} which does not exist")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BinaryMeshError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BinaryMeshError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+// Vue sur un fichier déjà en mémoire, sans aucune copie
+pub struct MeshView<'a> {
+    pub header: &'a Header,
+    pub vertices: &'a [VertexData],
+    pub indices: Indices<'a>,
+    pub sub_meshes: &'a [SubMesh],
+}
+
+impl<'a> MeshView<'a> {
+    // `bytes` doit être aligné sur 4 octets, ce que garantit `MeshFile`
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, BinaryMeshError> {
+        let header_size = std::mem::size_of::<Header>();
+        let header_bytes = bytes.get(..header_size).ok_or(BinaryMeshError::Truncated)?;
+        let header: &Header = bytemuck::try_from_bytes(header_bytes).map_err(|_| BinaryMeshError::Misaligned)?;
+
+        if header.magic != MAGIC {
+            return Err(BinaryMeshError::BadMagic);
+        }
+
+        if header.version != VERSION {
+            return Err(BinaryMeshError::UnsupportedVersion(header.version));
+        }
+
+        if header.vertex_stride as usize != std::mem::size_of::<VertexData>() {
+            return Err(BinaryMeshError::VertexStrideMismatch(header.vertex_stride));
+        }
+
+        let index_size = match header.index_size {
+            2 | 4 => header.index_size as usize,
+            size => return Err(BinaryMeshError::InvalidIndexSize(size)),
+        };
+
+        // Avec un usize de 32 bits (wasm), un en-tête forgé peut faire déborder
+        // ces calculs : le fichier ne peut alors pas contenir autant de données
+        let end = |start: usize, count: u32, size: usize| {
+            (count as usize)
+                .checked_mul(size)
+                .and_then(|section_size| start.checked_add(section_size))
+                .ok_or(BinaryMeshError::Truncated)
+        };
+
+        let vertices_end = end(header_size, header.vertex_count, std::mem::size_of::<VertexData>())?;
+        let indices_end = end(vertices_end, header.index_count, index_size)?;
+        // Le début des indices est aligné, il suffit d'aligner leur fin
+        let sub_meshes_start = indices_end.checked_next_multiple_of(4).ok_or(BinaryMeshError::Truncated)?;
+        let sub_meshes_end = end(sub_meshes_start, header.sub_mesh_count, std::mem::size_of::<SubMesh>())?;
+
+        let section = |start: usize, end: usize| bytes.get(start..end).ok_or(BinaryMeshError::Truncated);
+        let vertex_bytes = section(header_size, vertices_end)?;
+        let index_bytes = section(vertices_end, indices_end)?;
+        let sub_mesh_bytes = section(sub_meshes_start, sub_meshes_end)?;
+
+        let indices = if index_size == 2 {
+            Indices::U16(bytemuck::try_cast_slice(index_bytes).map_err(|_| BinaryMeshError::Misaligned)?)
+        } else {
+            Indices::U32(bytemuck::try_cast_slice(index_bytes).map_err(|_| BinaryMeshError::Misaligned)?)
+        };
+
+        let view = Self {
+            header,
+            vertices: bytemuck::try_cast_slice(vertex_bytes).map_err(|_| BinaryMeshError::Misaligned)?,
+            indices,
+            sub_meshes: bytemuck::try_cast_slice(sub_mesh_bytes).map_err(|_| BinaryMeshError::Misaligned)?,
+        };
+
+        view.validate()?;
+        Ok(view)
+    }
+
+    // Un fichier corrompu doit être refusé ici plutôt que de faire paniquer
+    // la validation de wgpu au moment du draw
+    fn validate(&self) -> Result<(), BinaryMeshError> {
+        for (i, sub_mesh) in self.sub_meshes.iter().enumerate() {
+            let start = sub_mesh.index_start as usize;
+            let end = start
+                .checked_add(sub_mesh.index_count as usize)
+                .filter(|&end| end <= self.indices.len())
+                .ok_or(BinaryMeshError::SubMeshOutOfBounds(i))?;
+
+            let bounds = match self.indices {
+                Indices::U16(indices) => min_max(indices[start..end].iter().map(|&index| index as i64)),
+                Indices::U32(indices) => min_max(indices[start..end].iter().map(|&index| index as i64)),
+            };
+
+            let Some((min, max)) = bounds else {
+                continue;
+            };
+
+            let base_vertex = sub_mesh.base_vertex as i64;
+
+            if min + base_vertex < 0 {
+                return Err(BinaryMeshError::IndexOutOfBounds {
+                    sub_mesh: i,
+                    index: min + base_vertex,
+                });
+            }
+
+            if max + base_vertex >= self.vertices.len() as i64 {
+                return Err(BinaryMeshError::IndexOutOfBounds {
+                    sub_mesh: i,
+                    index: max + base_vertex,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        Aabb::new(Vec3::from(self.header.aabb_min), Vec3::from(self.header.aabb_max))
+    }
+
+    pub fn to_mesh(&self) -> Mesh {
+        Mesh {
+            vertices: self.vertices.to_vec(),
+            indices: self.indices.to_vec(),
+        }
+    }
+}
+
+// Contenu d'un fichier .lmesh validé, stocké dans un buffer aligné pour que
+// `view` puisse caster les sommets et les indices directement
+pub struct MeshFile {
+    words: Vec<u32>,
+}
+
+impl MeshFile {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BinaryMeshError> {
+        let mut file = fs::File::open(path)?;
+        let size = file.metadata()?.len() as usize;
+
+        // Tous les blocs du format font un multiple de 4 octets
+        if !size.is_multiple_of(4) {
+            return Err(BinaryMeshError::Misaligned);
+        }
+
+        let mut words = vec![0u32; size / 4];
+        file.read_exact(bytemuck::cast_slice_mut(&mut words))?;
+
+        Self::from_words(words)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BinaryMeshError> {
+        if !bytes.len().is_multiple_of(4) {
+            return Err(BinaryMeshError::Misaligned);
+        }
+
+        let word_count = bytes.len() / 4;
+        let mut words = vec![0u32; word_count];
+        bytemuck::cast_slice_mut(&mut words).copy_from_slice(&bytes[..word_count * 4]);
+
+        Self::from_words(words)
+    }
+
+    pub fn from_mesh(mesh: &Mesh, sub_meshes: &[SubMesh]) -> Self {
+        Self::from_bytes(&encode(mesh, sub_meshes)).unwrap()
+    }
+
+    fn from_words(words: Vec<u32>) -> Result<Self, BinaryMeshError> {
+        MeshView::parse(bytemuck::cast_slice(&words))?;
+        Ok(Self { words })
+    }
+
+    pub fn view(&self) -> MeshView<'_> {
+        MeshView::parse(bytemuck::cast_slice(&self.words)).unwrap()
+    }
+}
+
+// Les indices sont écrits en 16 bits quand le nombre de sommets le permet
+pub fn encode(mesh: &Mesh, sub_meshes: &[SubMesh]) -> Vec<u8> {
+    let aabb = mesh.aabb();
+    let (aabb_min, aabb_max) = if aabb.is_empty() {
+        (Vec3::ZERO, Vec3::ZERO)
+    } else {
+        (aabb.min, aabb.max)
+    };
+
+    let short_indices: Option<Vec<u16>> =
+        fits_u16_indices(mesh.vertices.len()).then(|| mesh.indices.iter().map(|&index| index as u16).collect());
+
+    let indices = match &short_indices {
+        Some(indices) => Indices::U16(indices),
+        None => Indices::U32(&mesh.indices),
+    };
+
+    let header = Header {
+        magic: MAGIC,
+        version: VERSION,
+        vertex_stride: std::mem::size_of::<VertexData>() as u32,
+        vertex_count: mesh.vertices.len() as u32,
+        index_count: indices.len() as u32,
+        index_size: if short_indices.is_some() { 2 } else { 4 },
+        sub_mesh_count: sub_meshes.len() as u32,
+        reserved: 0,
+        aabb_min: aabb_min.into(),
+        aabb_max: aabb_max.into(),
+    };
+
+    let index_bytes = indices.as_bytes();
+
+    let mut bytes = Vec::with_capacity(
+        std::mem::size_of::<Header>()
+            + std::mem::size_of_val(mesh.vertices.as_slice())
+            + padded(index_bytes.len())
+            + std::mem::size_of_val(sub_meshes),
+    );
+
+    bytes.extend_from_slice(bytemuck::bytes_of(&header));
+    bytes.extend_from_slice(bytemuck::cast_slice(&mesh.vertices));
+    bytes.extend_from_slice(index_bytes);
+    bytes.resize(bytes.len() + padded(index_bytes.len()) - index_bytes.len(), 0);
+    bytes.extend_from_slice(bytemuck::cast_slice(sub_meshes));
+    bytes
+}
+
+pub fn save(mesh: &Mesh, sub_meshes: &[SubMesh], path: impl AsRef<Path>) -> io::Result<()> {
+    fs::write(path, encode(mesh, sub_meshes))
+}
+
+fn min_max(values: impl Iterator<Item = i64>) -> Option<(i64, i64)> {
+    values.fold(None, |bounds, value| match bounds {
+        Some((min, max)) => Some((value.min(min), value.max(max))),
+        None => Some((value, value)),
+    })
+}
+
+fn padded(size: usize) -> usize {
+    (size + 3) & !3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh::primitives;
+
+    // Deux sous-meshes : un cube et une sphère décalée dans le même buffer
+    fn two_sub_meshes() -> (Mesh, Vec<SubMesh>) {
+        let mut mesh = Mesh::default();
+        let cube = mesh.append_sub_mesh(&primitives::cube(Vec3::ONE, 1), 0);
+        let sphere = mesh.append_sub_mesh(&primitives::uv_sphere(0.5, 8, 4), 1);
+
+        (mesh, vec![cube, sphere])
+    }
+
+    fn set_u32(bytes: &mut [u8], offset: usize, value: u32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn assert_round_trip(mesh: &Mesh, sub_meshes: &[SubMesh]) -> usize {
+        let file = MeshFile::from_bytes(&encode(mesh, sub_meshes)).unwrap();
+        let view = file.view();
+
+        assert_eq!(view.vertices, mesh.vertices.as_slice());
+        assert_eq!(view.indices.to_vec(), mesh.indices);
+        assert_eq!(view.sub_meshes, sub_meshes);
+        assert_eq!(view.aabb(), mesh.aabb());
+
+        view.header.index_size as usize
+    }
+
+    #[test]
+    fn round_trip_u16_indices() {
+        let mesh = primitives::cube(Vec3::ONE, 2);
+        let index_size = assert_round_trip(&mesh, &[SubMesh::whole(&mesh)]);
+
+        assert_eq!(index_size, 2);
+    }
+
+    #[test]
+    fn round_trip_u32_indices() {
+        let mut mesh = Mesh {
+            vertices: vec![VertexData::new([0.0; 3], [0.0, 1.0, 0.0]); 70_000],
+            indices: vec![0, 1, 69_999],
+        };
+        mesh.vertices[69_999].position = [1.0, 2.0, 3.0];
+
+        let index_size = assert_round_trip(&mesh, &[SubMesh::whole(&mesh)]);
+        assert_eq!(index_size, 4);
+    }
+
+    #[test]
+    fn round_trip_sub_meshes() {
+        let (mesh, sub_meshes) = two_sub_meshes();
+        assert_round_trip(&mesh, &sub_meshes);
+    }
+
+    #[test]
+    fn odd_index_count_is_padded() {
+        let mesh = Mesh {
+            vertices: vec![VertexData::new([0.0; 3], [0.0, 1.0, 0.0]); 3],
+            indices: vec![0, 1, 2],
+        };
+
+        assert_eq!(encode(&mesh, &[SubMesh::whole(&mesh)]).len() % 4, 0);
+        assert_round_trip(&mesh, &[SubMesh::whole(&mesh)]);
+    }
+
+    #[test]
+    fn bad_magic() {
+        let mut bytes = encode(&primitives::cube(Vec3::ONE, 1), &[]);
+        bytes[..4].copy_from_slice(b"OBJ ");
+
+        assert!(matches!(MeshFile::from_bytes(&bytes), Err(BinaryMeshError::BadMagic)));
+    }
+
+    #[test]
+    fn wrong_version() {
+        let mut bytes = encode(&primitives::cube(Vec3::ONE, 1), &[]);
+        set_u32(&mut bytes, 4, VERSION + 1);
+
+        assert!(matches!(
+            MeshFile::from_bytes(&bytes),
+            Err(BinaryMeshError::UnsupportedVersion(version)) if version == VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn truncated() {
+        let (mesh, sub_meshes) = two_sub_meshes();
+        let bytes = encode(&mesh, &sub_meshes);
+
+        // Dans l'en-tête, les sommets, les indices et les sous-meshes
+        for length in [0, 16, std::mem::size_of::<Header>() + 32, bytes.len() / 2, bytes.len() - 4] {
+            assert!(
+                matches!(MeshFile::from_bytes(&bytes[..length]), Err(BinaryMeshError::Truncated)),
+                "{length}"
+            );
+        }
+    }
+
+    #[test]
+    fn length_not_multiple_of_four() {
+        let mut bytes = encode(&primitives::cube(Vec3::ONE, 1), &[]);
+        bytes.push(0);
+
+        assert!(matches!(MeshFile::from_bytes(&bytes), Err(BinaryMeshError::Misaligned)));
+
+        let path = std::env::temp_dir().join(format!("lux_misaligned_{}.lmesh", std::process::id()));
+        fs::write(&path, &bytes).unwrap();
+        let result = MeshFile::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(BinaryMeshError::Misaligned)));
+    }
+
+    #[test]
+    fn huge_counts_are_truncated() {
+        // vertex_count, index_count puis sub_mesh_count
+        for offset in [12, 16, 24] {
+            let mut bytes = encode(&primitives::cube(Vec3::ONE, 1), &[]);
+            set_u32(&mut bytes, offset, u32::MAX);
+
+            assert!(matches!(MeshFile::from_bytes(&bytes), Err(BinaryMeshError::Truncated)), "{offset}");
+        }
+    }
+
+    #[test]
+    fn sub_mesh_out_of_index_buffer() {
+        let (mesh, mut sub_meshes) = two_sub_meshes();
+        sub_meshes[1].index_count += 3;
+
+        assert!(matches!(
+            MeshFile::from_bytes(&encode(&mesh, &sub_meshes)),
+            Err(BinaryMeshError::SubMeshOutOfBounds(1))
+        ));
+
+        sub_meshes[1].index_start = u32::MAX;
+        assert!(matches!(
+            MeshFile::from_bytes(&encode(&mesh, &sub_meshes)),
+            Err(BinaryMeshError::SubMeshOutOfBounds(1))
+        ));
+    }
+
+    #[test]
+    fn index_out_of_vertex_buffer() {
+        let (mut mesh, mut sub_meshes) = two_sub_meshes();
+        let vertex_count = mesh.vertices.len() as i64;
+
+        sub_meshes[0].base_vertex = 1_000;
+        assert!(matches!(
+            MeshFile::from_bytes(&encode(&mesh, &sub_meshes)),
+            Err(BinaryMeshError::IndexOutOfBounds { sub_mesh: 0, .. })
+        ));
+
+        sub_meshes[0].base_vertex = -1;
+        assert!(matches!(
+            MeshFile::from_bytes(&encode(&mesh, &sub_meshes)),
+            Err(BinaryMeshError::IndexOutOfBounds { sub_mesh: 0, index: -1 })
+        ));
+
+        sub_meshes[0].base_vertex = 0;
+        *mesh.indices.last_mut().unwrap() = vertex_count as u32;
+        assert!(matches!(
+            MeshFile::from_bytes(&encode(&mesh, &sub_meshes)),
+            Err(BinaryMeshError::IndexOutOfBounds { sub_mesh: 1, index }) if index == vertex_count
+        ));
+    }
+}
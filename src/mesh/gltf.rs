@@ -0,0 +1,383 @@
+use std::path::Path;
+
+use glam::{Mat4, Quat, Vec3, Vec4};
+
+use super::{Mesh, VertexData};
+use crate::{
+    anim::Interpolation,
+    material::Material,
+    skin::{AnimationClip, Channel, ChannelValues, Joint, Skeleton, SkinVertex},
+    transform::Transform,
+};
+
+pub use ::gltf::Error;
+
+pub struct GltfModel {
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<GltfMaterial>,
+    pub textures: Vec<GltfTexture>,
+    pub nodes: Vec<GltfNode>,
+    pub skins: Vec<GltfSkin>,
+    // Noeuds racines de la scène par défaut
+    pub roots: Vec<usize>,
+}
+
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+pub struct GltfPrimitive {
+    pub mesh: Mesh,
+    pub material: Option<usize>,
+    // Un élément par sommet si le mesh est animé par un squelette
+    pub skin: Option<Vec<SkinVertex>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfMaterial {
+    pub name: Option<String>,
+    pub base_color: Vec4,
+    pub base_color_texture: Option<usize>,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub metallic_roughness_texture: Option<usize>,
+    pub normal_texture: Option<usize>,
+    pub emissive: Vec3,
+    pub double_sided: bool,
+}
+
+impl GltfMaterial {
+    pub fn to_material(&self) -> Material {
+        Material::pbr(self.base_color.truncate(), self.metallic, self.roughness)
+    }
+}
+
+// Pixels toujours convertis en RGBA8
+pub struct GltfTexture {
+    pub name: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfNode {
+    pub name: Option<String>,
+    pub transform: Mat4,
+    pub mesh: Option<usize>,
+    pub skin: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+// Les animations du fichier qui touchent ce squelette, réduites à ses articulations
+pub struct GltfSkin {
+    pub name: Option<String>,
+    pub skeleton: Skeleton,
+    // Noeud correspondant à chaque articulation
+    pub joint_nodes: Vec<usize>,
+    pub clips: Vec<AnimationClip>,
+}
+
+impl GltfModel {
+    // Accepte les .gltf (buffers et images externes ou en data URI) comme les .glb
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let (document, buffers, images) = ::gltf::import(path)?;
+        Ok(Self::from_document(&document, &buffers, &images))
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        let (document, buffers, images) = ::gltf::import_slice(bytes)?;
+        Ok(Self::from_document(&document, &buffers, &images))
+    }
+
+    fn from_document(
+        document: &::gltf::Document,
+        buffers: &[::gltf::buffer::Data],
+        images: &[::gltf::image::Data],
+    ) -> Self {
+        let meshes = document
+            .meshes()
+            .map(|mesh| GltfMesh {
+                name: mesh.name().map(str::to_owned),
+                primitives: mesh
+                    .primitives()
+                    .filter(|primitive| primitive.mode() == ::gltf::mesh::Mode::Triangles)
+                    .filter_map(|primitive| load_primitive(&primitive, buffers))
+                    .collect(),
+            })
+            .collect();
+
+        let materials = document
+            .materials()
+            .map(|material| {
+                let pbr = material.pbr_metallic_roughness();
+
+                GltfMaterial {
+                    name: material.name().map(str::to_owned),
+                    base_color: Vec4::from(pbr.base_color_factor()),
+                    base_color_texture: pbr.base_color_texture().map(|info| info.texture().index()),
+                    metallic: pbr.metallic_factor(),
+                    roughness: pbr.roughness_factor(),
+                    metallic_roughness_texture: pbr
+                        .metallic_roughness_texture()
+                        .map(|info| info.texture().index()),
+                    normal_texture: material.normal_texture().map(|info| info.texture().index()),
+                    emissive: Vec3::from(material.emissive_factor()),
+                    double_sided: material.double_sided(),
+                }
+            })
+            .collect();
+
+        let textures = document
+            .textures()
+            .map(|texture| {
+                let image = &images[texture.source().index()];
+
+                GltfTexture {
+                    name: texture.name().map(str::to_owned),
+                    width: image.width,
+                    height: image.height,
+                    pixels: to_rgba8(image),
+                }
+            })
+            .collect();
+
+        let nodes = document
+            .nodes()
+            .map(|node| GltfNode {
+                name: node.name().map(str::to_owned),
+                transform: Mat4::from_cols_array_2d(&node.transform().matrix()),
+                mesh: node.mesh().map(|mesh| mesh.index()),
+                skin: node.skin().map(|skin| skin.index()),
+                children: node.children().map(|child| child.index()).collect(),
+            })
+            .collect();
+
+        let skins = document.skins().map(|skin| load_skin(document, &skin, buffers)).collect();
+
+        let roots = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .map(|scene| scene.nodes().map(|node| node.index()).collect())
+            .unwrap_or_default();
+
+        Self {
+            meshes,
+            materials,
+            textures,
+            nodes,
+            skins,
+            roots,
+        }
+    }
+
+    // Matrices monde de chaque noeud, dans le même ordre que `nodes`
+    pub fn world_transforms(&self) -> Vec<Mat4> {
+        let mut transforms = vec![Mat4::IDENTITY; self.nodes.len()];
+        let mut stack: Vec<(usize, Mat4)> = self.roots.iter().map(|&root| (root, Mat4::IDENTITY)).collect();
+
+        while let Some((index, parent)) = stack.pop() {
+            let node = &self.nodes[index];
+            transforms[index] = parent * node.transform;
+            stack.extend(node.children.iter().map(|&child| (child, transforms[index])));
+        }
+
+        transforms
+    }
+}
+
+fn load_primitive(primitive: &::gltf::Primitive, buffers: &[::gltf::buffer::Data]) -> Option<GltfPrimitive> {
+    let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data.0[..]));
+
+    let mut vertices: Vec<VertexData> = reader
+        .read_positions()?
+        .map(|position| VertexData::new(position, [0.0, 0.0, 0.0]))
+        .collect();
+
+    let indices = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..vertices.len() as u32).collect(),
+    };
+
+    let has_normals = match reader.read_normals() {
+        Some(normals) => {
+            for (vertex, normal) in vertices.iter_mut().zip(normals) {
+                vertex.normal = normal;
+            }
+            true
+        }
+        None => false,
+    };
+
+    let has_uvs = match reader.read_tex_coords(0) {
+        Some(uvs) => {
+            for (vertex, uv) in vertices.iter_mut().zip(uvs.into_f32()) {
+                vertex.uv = uv;
+            }
+            true
+        }
+        None => false,
+    };
+
+    let has_tangents = match reader.read_tangents() {
+        Some(tangents) => {
+            for (vertex, tangent) in vertices.iter_mut().zip(tangents) {
+                vertex.tangent = tangent;
+            }
+            true
+        }
+        None => false,
+    };
+
+    let skin = reader.read_joints(0).zip(reader.read_weights(0)).map(|(joints, weights)| {
+        joints
+            .into_u16()
+            .zip(weights.into_f32())
+            .map(|(joints, weights)| SkinVertex { joints, weights })
+            .collect()
+    });
+
+    let mut mesh = Mesh { vertices, indices };
+
+    if !has_normals {
+        mesh.compute_smooth_normals();
+    }
+
+    if has_uvs && !has_tangents {
+        mesh.compute_tangents();
+    }
+
+    Some(GltfPrimitive {
+        mesh,
+        material: primitive.material().index(),
+        skin,
+    })
+}
+
+fn load_skin(document: &::gltf::Document, skin: &::gltf::Skin, buffers: &[::gltf::buffer::Data]) -> GltfSkin {
+    let get_buffer = |buffer: ::gltf::Buffer| buffers.get(buffer.index()).map(|data| &data.0[..]);
+
+    let joint_nodes: Vec<usize> = skin.joints().map(|node| node.index()).collect();
+    let joint_of_node = |node: usize| joint_nodes.iter().position(|&joint_node| joint_node == node);
+
+    let mut node_parents = vec![None; document.nodes().len()];
+    for node in document.nodes() {
+        for child in node.children() {
+            node_parents[child.index()] = Some(node.index());
+        }
+    }
+
+    let inverse_bind_matrices: Vec<Mat4> = skin
+        .reader(get_buffer)
+        .read_inverse_bind_matrices()
+        .map(|matrices| matrices.map(|matrix| Mat4::from_cols_array_2d(&matrix)).collect())
+        .unwrap_or_default();
+
+    let joints = skin
+        .joints()
+        .enumerate()
+        .map(|(index, node)| {
+            let (translation, rotation, scale) = node.transform().decomposed();
+
+            Joint {
+                name: node.name().map(str::to_owned),
+                parent: node_parents[node.index()].and_then(joint_of_node),
+                rest: Transform {
+                    translation: Vec3::from(translation),
+                    rotation: Quat::from_array(rotation),
+                    scale: Vec3::from(scale),
+                },
+                inverse_bind_matrix: inverse_bind_matrices.get(index).copied().unwrap_or(Mat4::IDENTITY),
+            }
+        })
+        .collect();
+
+    let clips = document
+        .animations()
+        .filter_map(|animation| {
+            let channels: Vec<Channel> = animation
+                .channels()
+                .filter_map(|channel| {
+                    use ::gltf::animation::util::ReadOutputs;
+
+                    let joint = joint_of_node(channel.target().node().index())?;
+                    let reader = channel.reader(get_buffer);
+
+                    let values = match reader.read_outputs()? {
+                        ReadOutputs::Translations(values) => ChannelValues::Translation(values.map(Vec3::from).collect()),
+                        ReadOutputs::Rotations(values) => {
+                            ChannelValues::Rotation(values.into_f32().map(Quat::from_array).collect())
+                        }
+                        ReadOutputs::Scales(values) => ChannelValues::Scale(values.map(Vec3::from).collect()),
+                        ReadOutputs::MorphTargetWeights(_) => return None,
+                    };
+
+                    Some(Channel {
+                        joint,
+                        interpolation: match channel.sampler().interpolation() {
+                            ::gltf::animation::Interpolation::Step => Interpolation::Step,
+                            ::gltf::animation::Interpolation::Linear => Interpolation::Linear,
+                            ::gltf::animation::Interpolation::CubicSpline => Interpolation::Cubic,
+                        },
+                        times: reader.read_inputs()?.collect(),
+                        values,
+                    })
+                })
+                .collect();
+
+            (!channels.is_empty()).then(|| AnimationClip::new(animation.name().map(str::to_owned), channels))
+        })
+        .collect();
+
+    GltfSkin {
+        name: skin.name().map(str::to_owned),
+        skeleton: Skeleton::new(joints),
+        joint_nodes,
+        clips,
+    }
+}
+
+fn to_rgba8(image: &::gltf::image::Data) -> Vec<u8> {
+    use ::gltf::image::Format;
+
+    let pixel_count = (image.width * image.height) as usize;
+    let mut rgba = Vec::with_capacity(pixel_count * 4);
+
+    // Pour les formats 16 et 32 bits on ne garde que l'octet de poids fort
+    let (channels, bytes_per_channel) = match image.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        Format::R32G32B32FLOAT => (3, 4),
+        Format::R32G32B32A32FLOAT => (4, 4),
+    };
+
+    let read_channel = |texel: &[u8], channel: usize| -> u8 {
+        let bytes = &texel[channel * bytes_per_channel..(channel + 1) * bytes_per_channel];
+
+        match bytes_per_channel {
+            1 => bytes[0],
+            2 => bytes[1],
+            _ => (f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]).clamp(0.0, 1.0) * 255.0) as u8,
+        }
+    };
+
+    for texel in image.pixels.chunks_exact(channels * bytes_per_channel).take(pixel_count) {
+        let r = read_channel(texel, 0);
+
+        match channels {
+            1 => rgba.extend_from_slice(&[r, r, r, 255]),
+            2 => rgba.extend_from_slice(&[r, read_channel(texel, 1), 0, 255]),
+            3 => rgba.extend_from_slice(&[r, read_channel(texel, 1), read_channel(texel, 2), 255]),
+            _ => rgba.extend_from_slice(&[r, read_channel(texel, 1), read_channel(texel, 2), read_channel(texel, 3)]),
+        }
+    }
+
+    rgba
+}
@@ -0,0 +1,499 @@
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use glam::Vec3;
+
+use super::{Mesh, VertexData};
+use crate::material::Material;
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io { path: PathBuf, error: io::Error },
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "failed to read \"{}\": {error}", path.display()),
+            Self::Parse { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { error, .. } => Some(error),
+            Self::Parse { .. } => None,
+        }
+    }
+}
+
+pub struct ObjModel {
+    pub meshes: Vec<ObjMesh>,
+    pub materials: Vec<ObjMaterial>,
+}
+
+// Un mesh par groupe (`o` / `g`) et par matériau utilisé dans ce groupe
+pub struct ObjMesh {
+    pub name: Option<String>,
+    pub mesh: Mesh,
+    pub material: Option<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ObjMaterial {
+    pub name: String,
+    pub ambient: Vec3,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+    pub emissive: Vec3,
+    pub shininess: f32,
+    pub dissolve: f32,
+    // Chemin tel qu'écrit dans le .mtl, relatif au fichier
+    pub diffuse_texture: Option<String>,
+    pub normal_texture: Option<String>,
+}
+
+impl Default for ObjMaterial {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            ambient: Vec3::ZERO,
+            diffuse: Vec3::ONE,
+            specular: Vec3::ZERO,
+            emissive: Vec3::ZERO,
+            shininess: 1.0,
+            dissolve: 1.0,
+            diffuse_texture: None,
+            normal_texture: None,
+        }
+    }
+}
+
+impl ObjMaterial {
+    // Le matériau Blinn-Phong n'a qu'une intensité spéculaire, on garde la
+    // composante la plus forte de Ks
+    pub fn to_material(&self) -> Material {
+        Material::phong(self.diffuse, self.specular.max_element(), self.shininess.max(1.0))
+    }
+}
+
+impl ObjModel {
+    // Les fichiers `mtllib` sont cherchés à côté du .obj
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ObjError> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or(Path::new(""));
+
+        Self::parse(&read_file(path)?, |name| read_file(&directory.join(name)))
+    }
+
+    pub fn parse(
+        source: &str,
+        mut load_mtl: impl FnMut(&str) -> Result<String, ObjError>,
+    ) -> Result<Self, ObjError> {
+        let mut positions: Vec<[f32; 3]> = Vec::new();
+        let mut normals: Vec<[f32; 3]> = Vec::new();
+        let mut uvs: Vec<[f32; 2]> = Vec::new();
+
+        let mut materials: Vec<ObjMaterial> = Vec::new();
+        let mut builders: Vec<MeshBuilder> = Vec::new();
+        let mut name: Option<String> = None;
+        let mut material: Option<usize> = None;
+
+        for (line_index, line) in source.lines().enumerate() {
+            let line_number = line_index + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let mut tokens = line.split_whitespace();
+
+            let Some(keyword) = tokens.next() else {
+                continue;
+            };
+
+            match keyword {
+                "v" => positions.push(parse_floats(tokens, line_number)?),
+                "vn" => normals.push(parse_floats(tokens, line_number)?),
+                "vt" => {
+                    let [u, v] = parse_floats::<2>(tokens.chain(["0.0"]), line_number)?;
+                    // L'origine des UV OBJ est en bas à gauche, celle de wgpu en haut à gauche
+                    uvs.push([u, 1.0 - v]);
+                }
+                "f" => {
+                    let face = tokens
+                        .map(|token| parse_face_vertex(token, &positions, &uvs, &normals, line_number))
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    if face.len() < 3 {
+                        return Err(parse_error(line_number, "face with less than 3 vertices"));
+                    }
+
+                    let needs_builder = !builders
+                        .last()
+                        .is_some_and(|builder| builder.name == name && builder.material == material);
+
+                    if needs_builder {
+                        builders.push(MeshBuilder::new(name.clone(), material));
+                    }
+
+                    builders.last_mut().unwrap().add_face(&face, &positions, &uvs, &normals);
+                }
+                "o" | "g" => name = Some(tokens.collect::<Vec<_>>().join(" ")).filter(|name| !name.is_empty()),
+                "usemtl" => {
+                    let material_name = tokens.collect::<Vec<_>>().join(" ");
+                    material = materials.iter().position(|material| material.name == material_name);
+                }
+                "mtllib" => {
+                    for file in tokens {
+                        materials.extend(parse_mtl(&load_mtl(file)?)?);
+                    }
+                }
+                // Groupes de lissage, lignes, points... ignorés
+                _ => {}
+            }
+        }
+
+        let meshes = builders
+            .into_iter()
+            .filter(|builder| !builder.mesh.indices.is_empty())
+            .map(MeshBuilder::build)
+            .collect();
+
+        Ok(Self { meshes, materials })
+    }
+}
+
+pub fn parse_mtl(source: &str) -> Result<Vec<ObjMaterial>, ObjError> {
+    let mut materials: Vec<ObjMaterial> = Vec::new();
+
+    for (line_index, line) in source.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let mut tokens = line.split_whitespace();
+
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+
+        if keyword == "newmtl" {
+            materials.push(ObjMaterial {
+                name: tokens.collect::<Vec<_>>().join(" "),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let Some(material) = materials.last_mut() else {
+            return Err(parse_error(line_number, "material property before \"newmtl\""));
+        };
+
+        match keyword {
+            "Ka" => material.ambient = Vec3::from(parse_floats(tokens, line_number)?),
+            "Kd" => material.diffuse = Vec3::from(parse_floats(tokens, line_number)?),
+            "Ks" => material.specular = Vec3::from(parse_floats(tokens, line_number)?),
+            "Ke" => material.emissive = Vec3::from(parse_floats(tokens, line_number)?),
+            "Ns" => [material.shininess] = parse_floats(tokens, line_number)?,
+            "d" => [material.dissolve] = parse_floats(tokens, line_number)?,
+            "Tr" => {
+                let [transparency] = parse_floats(tokens, line_number)?;
+                material.dissolve = 1.0 - transparency;
+            }
+            // Les options éventuelles (-bm, -o...) précèdent toujours le chemin
+            "map_Kd" => material.diffuse_texture = tokens.last().map(str::to_owned),
+            "map_Bump" | "map_bump" | "bump" | "norm" => {
+                material.normal_texture = tokens.last().map(str::to_owned)
+            }
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+struct FaceVertex {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+struct MeshBuilder {
+    name: Option<String>,
+    material: Option<usize>,
+    mesh: Mesh,
+    // Un sommet par combinaison position/uv/normale distincte
+    vertex_indices: HashMap<FaceVertex, u32>,
+    // Sommets sans normale dans le fichier
+    missing_normals: Vec<u32>,
+    has_uvs: bool,
+}
+
+impl MeshBuilder {
+    fn new(name: Option<String>, material: Option<usize>) -> Self {
+        Self {
+            name,
+            material,
+            mesh: Mesh::default(),
+            vertex_indices: HashMap::new(),
+            missing_normals: Vec::new(),
+            has_uvs: false,
+        }
+    }
+
+    fn add_face(&mut self, face: &[FaceVertex], positions: &[[f32; 3]], uvs: &[[f32; 2]], normals: &[[f32; 3]]) {
+        let indices: Vec<u32> = face
+            .iter()
+            .map(|face_vertex| self.vertex_index(*face_vertex, positions, uvs, normals))
+            .collect();
+
+        // Triangulation en éventail, suffisante pour les polygones convexes
+        for i in 1..indices.len() - 1 {
+            self.mesh.indices.extend_from_slice(&[indices[0], indices[i], indices[i + 1]]);
+        }
+    }
+
+    fn vertex_index(
+        &mut self,
+        face_vertex: FaceVertex,
+        positions: &[[f32; 3]],
+        uvs: &[[f32; 2]],
+        normals: &[[f32; 3]],
+    ) -> u32 {
+        if let Some(&index) = self.vertex_indices.get(&face_vertex) {
+            return index;
+        }
+
+        let mut vertex = VertexData::new(positions[face_vertex.position], [0.0, 0.0, 0.0]);
+
+        if let Some(uv) = face_vertex.uv {
+            vertex.uv = uvs[uv];
+            self.has_uvs = true;
+        }
+
+        let index = self.mesh.vertices.len() as u32;
+
+        match face_vertex.normal {
+            Some(normal) => vertex.normal = normals[normal],
+            None => self.missing_normals.push(index),
+        }
+
+        self.mesh.vertices.push(vertex);
+        self.vertex_indices.insert(face_vertex, index);
+        index
+    }
+
+    fn build(mut self) -> ObjMesh {
+        // Les normales lissées ne remplacent que celles qui manquent
+        if !self.missing_normals.is_empty() {
+            let mut smooth = self.mesh.clone();
+            smooth.compute_smooth_normals();
+
+            for &index in &self.missing_normals {
+                self.mesh.vertices[index as usize].normal = smooth.vertices[index as usize].normal;
+            }
+        }
+
+        if self.has_uvs {
+            self.mesh.compute_tangents();
+        }
+
+        ObjMesh {
+            name: self.name,
+            mesh: self.mesh,
+            material: self.material,
+        }
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, ObjError> {
+    fs::read_to_string(path).map_err(|error| ObjError::Io {
+        path: path.to_owned(),
+        error,
+    })
+}
+
+fn parse_error(line: usize, message: impl Into<String>) -> ObjError {
+    ObjError::Parse {
+        line,
+        message: message.into(),
+    }
+}
+
+// Les composantes en trop (ex: le w des positions) sont ignorées
+fn parse_floats<'s, const N: usize>(
+    mut tokens: impl Iterator<Item = &'s str>,
+    line: usize,
+) -> Result<[f32; N], ObjError> {
+    let mut values = [0.0; N];
+
+    for value in &mut values {
+        let token = tokens
+            .next()
+            .ok_or_else(|| parse_error(line, format!("expected {N} numbers")))?;
+
+        *value = token
+            .parse()
+            .map_err(|_| parse_error(line, format!("invalid number \"{token}\"")))?;
+    }
+
+    Ok(values)
+}
+
+// `v`, `v/vt`, `v//vn` ou `v/vt/vn`, les indices négatifs partent de la fin
+fn parse_face_vertex(
+    token: &str,
+    positions: &[[f32; 3]],
+    uvs: &[[f32; 2]],
+    normals: &[[f32; 3]],
+    line: usize,
+) -> Result<FaceVertex, ObjError> {
+    let mut parts = token.split('/');
+
+    let resolve = |part: Option<&str>, count: usize| -> Result<Option<usize>, ObjError> {
+        let Some(part) = part.filter(|part| !part.is_empty()) else {
+            return Ok(None);
+        };
+
+        let index: i64 = part
+            .parse()
+            .map_err(|_| parse_error(line, format!("invalid index \"{part}\"")))?;
+
+        let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+
+        if resolved < 0 || resolved >= count as i64 {
+            return Err(parse_error(line, format!("index {index} out of range")));
+        }
+
+        Ok(Some(resolved as usize))
+    };
+
+    let position = resolve(parts.next(), positions.len())?
+        .ok_or_else(|| parse_error(line, format!("missing position index in \"{token}\"")))?;
+    let uv = resolve(parts.next(), uvs.len())?;
+    let normal = resolve(parts.next(), normals.len())?;
+
+    Ok(FaceVertex { position, uv, normal })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_fixture(name: &str) -> Result<ObjModel, ObjError> {
+        ObjModel::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name))
+    }
+
+    fn mesh<'a>(model: &'a ObjModel, name: &str) -> &'a Mesh {
+        &model.meshes.iter().find(|mesh| mesh.name.as_deref() == Some(name)).unwrap().mesh
+    }
+
+    fn positions(mesh: &Mesh) -> Vec<[f32; 3]> {
+        mesh.indices.iter().map(|&index| mesh.vertices[index as usize].position).collect()
+    }
+
+    #[test]
+    fn face_formats() {
+        let model = load_fixture("face_formats.obj").unwrap();
+        assert_eq!(model.meshes.len(), 4);
+
+        let full = mesh(&model, "full");
+        assert_eq!(positions(full), [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]]);
+        // V est retourné pour wgpu
+        assert_eq!(full.vertices[0].uv, [0.0, 1.0]);
+        assert_eq!(full.vertices[2].uv, [1.0, 0.0]);
+        assert!(full.vertices.iter().all(|vertex| vertex.normal == [0.6, 0.0, 0.8]));
+
+        let normals_only = mesh(&model, "normals_only");
+        assert!(normals_only.vertices.iter().all(|vertex| vertex.normal == [0.0, 0.0, 1.0]));
+        assert!(normals_only.vertices.iter().all(|vertex| vertex.uv == [0.0, 0.0]));
+
+        let positions_only = mesh(&model, "positions_only");
+        assert_eq!(positions(positions_only), [[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]);
+        assert!(positions_only.vertices.iter().all(|vertex| vertex.normal == [0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn missing_normals_keep_file_normals() {
+        let model = load_fixture("face_formats.obj").unwrap();
+        let mixed = mesh(&model, "mixed");
+
+        assert_eq!(mixed.vertices[0].normal, [0.6, 0.0, 0.8]);
+        assert_eq!(mixed.vertices[1].normal, [0.0, 0.0, 1.0]);
+        assert_eq!(mixed.vertices[2].normal, [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn negative_indices() {
+        let model = load_fixture("negative_indices.obj").unwrap();
+        assert_eq!(model.meshes.len(), 1);
+
+        let mesh = &model.meshes[0].mesh;
+        assert_eq!(
+            positions(mesh),
+            [
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [5.0, 0.0, 0.0],
+                [6.0, 0.0, 0.0],
+                [5.0, 1.0, 0.0],
+            ]
+        );
+        assert_eq!(mesh.vertices[0].normal, [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn out_of_range_index() {
+        let result = ObjModel::parse("v 0 0 0\nv 1 0 0\nf 1 2 3\n", |_| unreachable!());
+        assert!(matches!(result, Err(ObjError::Parse { line: 3, .. })));
+
+        let result = ObjModel::parse("v 0 0 0\nf -2 1 1\n", |_| unreachable!());
+        assert!(matches!(result, Err(ObjError::Parse { line: 2, .. })));
+    }
+
+    #[test]
+    fn polygons_are_fan_triangulated() {
+        let model = load_fixture("polygons.obj").unwrap();
+        let mesh = &model.meshes[0].mesh;
+
+        assert_eq!(mesh.vertices.len(), 9);
+        assert_eq!(mesh.indices, [0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7, 4, 7, 8]);
+    }
+
+    #[test]
+    fn usemtl_groups() {
+        let model = load_fixture("materials.obj").unwrap();
+
+        let names: Vec<&str> = model.materials.iter().map(|material| material.name.as_str()).collect();
+        assert_eq!(names, ["red", "blue"]);
+
+        // Un mesh à chaque changement de matériau, un matériau inconnu n'en a pas
+        let materials: Vec<Option<usize>> = model.meshes.iter().map(|mesh| mesh.material).collect();
+        assert_eq!(materials, [Some(0), Some(1), Some(0), None]);
+        assert!(model.meshes.iter().all(|mesh| mesh.name.as_deref() == Some("box")));
+        assert!(model.meshes.iter().all(|mesh| mesh.mesh.indices.len() == 3));
+
+        let red = model.materials[0].to_material();
+        assert_eq!(red.base_color, Vec3::X);
+        assert_eq!(red.specular_strength, 0.5);
+        assert_eq!(red.shininess, 64.0);
+
+        let blue = &model.materials[1];
+        assert_eq!(blue.diffuse, Vec3::Z);
+        assert_eq!(blue.diffuse_texture.as_deref(), Some("textures/blue.png"));
+    }
+
+    #[test]
+    fn missing_mtl_file() {
+        match load_fixture("missing_mtl.obj") {
+            Err(ObjError::Io { path, error }) => {
+                assert!(path.ends_with("missing.mtl"));
+                assert_eq!(error.kind(), io::ErrorKind::NotFound);
+            }
+            _ => panic!("expected an I/O error for the missing .mtl"),
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use std::{
+    collections::HashMap,
+    f32::consts::{FRAC_PI_2, PI, TAU},
+};
+
+use glam::{vec3, Vec2, Vec3};
+
+use super::{Mesh, VertexData};
+
+// Toutes les formes sont centrées sur l'origine, avec des triangles dans le
+// sens trigonométrique vus de l'extérieur. Les UV ont leur origine en haut à
+// gauche et la tangente suit le sens des u croissants.
+
+pub fn cube(size: Vec3, segments: u32) -> Mesh {
+    let segments = segments.max(1);
+    let mut mesh = Mesh::default();
+
+    // Normale, axe des u et axe des v, avec u x v = normale
+    let faces = [
+        (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+        (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+        (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+        (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+        (Vec3::Z, Vec3::X, Vec3::Y),
+        (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+    ];
+
+    for (normal, u_axis, v_axis) in faces {
+        add_grid(&mut mesh, segments, segments, |column, row| {
+            let u = column as f32 / segments as f32;
+            let v = row as f32 / segments as f32;
+            let position = (normal * 0.5 + u_axis * (u - 0.5) + v_axis * (v - 0.5)) * size;
+
+            vertex(position, normal, [u, 1.0 - v], u_axis)
+        });
+    }
+
+    mesh
+}
+
+// Plan horizontal, face vers +Y
+pub fn plane(size: Vec2, subdivisions: u32) -> Mesh {
+    let segments = subdivisions + 1;
+    let mut mesh = Mesh::default();
+
+    add_grid(&mut mesh, segments, segments, |column, row| {
+        let u = column as f32 / segments as f32;
+        let v = row as f32 / segments as f32;
+        let position = vec3((u - 0.5) * size.x, 0.0, (0.5 - v) * size.y);
+
+        vertex(position, Vec3::Y, [u, 1.0 - v], Vec3::X)
+    });
+
+    mesh
+}
+
+pub fn uv_sphere(radius: f32, sectors: u32, stacks: u32) -> Mesh {
+    let sectors = sectors.max(3);
+    let stacks = stacks.max(2);
+    let mut mesh = Mesh::default();
+
+    add_grid(&mut mesh, sectors, stacks, |column, row| {
+        let u = column as f32 / sectors as f32;
+        let v = row as f32 / stacks as f32;
+        let normal = spherical(u * TAU, PI * (1.0 - v));
+
+        vertex(normal * radius, normal, [u, 1.0 - v], around_y(u * TAU))
+    });
+
+    mesh
+}
+
+pub fn icosphere(radius: f32, subdivisions: u32) -> Mesh {
+    let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+
+    let mut positions: Vec<Vec3> = [
+        (-1.0, t, 0.0),
+        (1.0, t, 0.0),
+        (-1.0, -t, 0.0),
+        (1.0, -t, 0.0),
+        (0.0, -1.0, t),
+        (0.0, 1.0, t),
+        (0.0, -1.0, -t),
+        (0.0, 1.0, -t),
+        (t, 0.0, -1.0),
+        (t, 0.0, 1.0),
+        (-t, 0.0, -1.0),
+        (-t, 0.0, 1.0),
+    ]
+    .into_iter()
+    .map(|(x, y, z)| vec3(x, y, z).normalize())
+    .collect();
+
+    #[rustfmt::skip]
+    let mut triangles: Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                positions.push(((positions[a as usize] + positions[b as usize]) * 0.5).normalize());
+                positions.len() as u32 - 1
+            })
+        };
+
+        triangles = triangles
+            .into_iter()
+            .flat_map(|[a, b, c]| {
+                let ab = midpoint(a, b);
+                let bc = midpoint(b, c);
+                let ca = midpoint(c, a);
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    // Les sommets sur la couture des UV et aux pôles sont dupliqués, la clé
+    // est l'index de la position et le u retenu pour ce triangle
+    let mut mesh = Mesh::default();
+    let mut vertex_indices: HashMap<(u32, u32), u32> = HashMap::new();
+
+    for triangle in triangles {
+        let mut us = triangle.map(|index| {
+            let position = positions[index as usize];
+            (position.x.atan2(position.z) / TAU).rem_euclid(1.0)
+        });
+
+        if us.iter().copied().fold(f32::MIN, f32::max) - us.iter().copied().fold(f32::MAX, f32::min) > 0.5 {
+            for u in &mut us {
+                if *u < 0.5 {
+                    *u += 1.0;
+                }
+            }
+        }
+
+        for corner in 0..3 {
+            if positions[triangle[corner] as usize].y.abs() > 0.9999 {
+                us[corner] = (us[(corner + 1) % 3] + us[(corner + 2) % 3]) * 0.5;
+            }
+        }
+
+        for (index, u) in triangle.into_iter().zip(us) {
+            let vertex_index = *vertex_indices.entry((index, u.to_bits())).or_insert_with(|| {
+                let normal = positions[index as usize];
+                let v = normal.y.clamp(-1.0, 1.0).acos() / PI;
+
+                mesh.vertices
+                    .push(vertex(normal * radius, normal, [u, v], around_y(u * TAU)));
+                mesh.vertices.len() as u32 - 1
+            });
+
+            mesh.indices.push(vertex_index);
+        }
+    }
+
+    mesh
+}
+
+// Cylindre vertical fermé, de hauteur totale `height`
+pub fn cylinder(radius: f32, height: f32, sectors: u32) -> Mesh {
+    let sectors = sectors.max(3);
+    let mut mesh = Mesh::default();
+
+    add_grid(&mut mesh, sectors, 1, |column, row| {
+        let u = column as f32 / sectors as f32;
+        let v = row as f32;
+        let normal = spherical(u * TAU, FRAC_PI_2);
+        let position = normal * radius + Vec3::Y * (v - 0.5) * height;
+
+        vertex(position, normal, [u, 1.0 - v], around_y(u * TAU))
+    });
+
+    add_disk(&mut mesh, Vec3::Y * height * 0.5, Vec3::Y, Vec3::X, radius, sectors);
+    add_disk(&mut mesh, Vec3::NEG_Y * height * 0.5, Vec3::NEG_Y, Vec3::X, radius, sectors);
+
+    mesh
+}
+
+// Cône vertical, pointe vers +Y
+pub fn cone(radius: f32, height: f32, sectors: u32) -> Mesh {
+    let sectors = sectors.max(3);
+    let mut mesh = Mesh::default();
+
+    add_grid(&mut mesh, sectors, 1, |column, row| {
+        let u = column as f32 / sectors as f32;
+        let v = row as f32;
+        let radial = spherical(u * TAU, FRAC_PI_2);
+        let position = radial * radius * (1.0 - v) + Vec3::Y * (v - 0.5) * height;
+        let normal = (radial * height + Vec3::Y * radius).normalize();
+
+        vertex(position, normal, [u, 1.0 - v], around_y(u * TAU))
+    });
+
+    add_disk(&mut mesh, Vec3::NEG_Y * height * 0.5, Vec3::NEG_Y, Vec3::X, radius, sectors);
+
+    mesh
+}
+
+// Tore couché dans le plan XZ
+pub fn torus(major_radius: f32, minor_radius: f32, major_segments: u32, minor_segments: u32) -> Mesh {
+    let major_segments = major_segments.max(3);
+    let minor_segments = minor_segments.max(3);
+    let mut mesh = Mesh::default();
+
+    add_grid(&mut mesh, major_segments, minor_segments, |column, row| {
+        let u = column as f32 / major_segments as f32;
+        let v = row as f32 / minor_segments as f32;
+        let radial = spherical(u * TAU, FRAC_PI_2);
+        let theta = v * TAU;
+        let normal = radial * theta.cos() + Vec3::Y * theta.sin();
+        let position = radial * major_radius + normal * minor_radius;
+
+        vertex(position, normal, [u, 1.0 - v], around_y(u * TAU))
+    });
+
+    mesh
+}
+
+// `height` est la longueur de la partie cylindrique, la hauteur totale vaut
+// `height + 2 * radius`. `stacks` est le nombre d'anneaux par hémisphère.
+pub fn capsule(radius: f32, height: f32, sectors: u32, stacks: u32) -> Mesh {
+    let sectors = sectors.max(3);
+    let stacks = stacks.max(1);
+    let total_height = height + 2.0 * radius;
+    let mut mesh = Mesh::default();
+
+    // Les anneaux 0..=stacks forment l'hémisphère bas, les suivants le haut,
+    // la bande entre les deux est le cylindre
+    add_grid(&mut mesh, sectors, 2 * stacks + 1, |column, row| {
+        let u = column as f32 / sectors as f32;
+
+        let (polar, offset) = if row <= stacks {
+            (PI - FRAC_PI_2 * row as f32 / stacks as f32, -0.5 * height)
+        } else {
+            (FRAC_PI_2 * (1.0 - (row - stacks - 1) as f32 / stacks as f32), 0.5 * height)
+        };
+
+        let normal = spherical(u * TAU, polar);
+        let position = normal * radius + Vec3::Y * offset;
+        let v = (position.y + total_height * 0.5) / total_height;
+
+        vertex(position, normal, [u, 1.0 - v], around_y(u * TAU))
+    });
+
+    mesh
+}
+
+fn vertex(position: Vec3, normal: Vec3, uv: [f32; 2], tangent: Vec3) -> VertexData {
+    VertexData {
+        position: position.into(),
+        normal: normal.into(),
+        uv,
+        tangent: tangent.extend(1.0).into(),
+    }
+}
+
+// `azimuth` part de +Z et tourne vers +X, `polar` part de +Y
+fn spherical(azimuth: f32, polar: f32) -> Vec3 {
+    vec3(polar.sin() * azimuth.sin(), polar.cos(), polar.sin() * azimuth.cos())
+}
+
+// Direction des u croissants sur une surface de révolution autour de Y
+fn around_y(azimuth: f32) -> Vec3 {
+    vec3(azimuth.cos(), 0.0, -azimuth.sin())
+}
+
+// Grille de (columns + 1) x (rows + 1) sommets, les lignes vont vers les v
+// croissants du paramétrage (donc les v de texture décroissants)
+fn add_grid(mesh: &mut Mesh, columns: u32, rows: u32, vertex: impl Fn(u32, u32) -> VertexData) {
+    let first = mesh.vertices.len() as u32;
+
+    for row in 0..=rows {
+        for column in 0..=columns {
+            mesh.vertices.push(vertex(column, row));
+        }
+    }
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let a = first + row * (columns + 1) + column;
+            let b = a + 1;
+            let c = b + columns + 1;
+            let d = a + columns + 1;
+
+            mesh.indices.extend_from_slice(&[a, b, c, a, c, d]);
+        }
+    }
+}
+
+fn add_disk(mesh: &mut Mesh, center: Vec3, normal: Vec3, u_axis: Vec3, radius: f32, sectors: u32) {
+    let v_axis = normal.cross(u_axis);
+    let first = mesh.vertices.len() as u32;
+
+    mesh.vertices.push(vertex(center, normal, [0.5, 0.5], u_axis));
+
+    for sector in 0..=sectors {
+        let angle = sector as f32 / sectors as f32 * TAU;
+        let (sin, cos) = angle.sin_cos();
+        let position = center + (u_axis * cos + v_axis * sin) * radius;
+
+        mesh.vertices
+            .push(vertex(position, normal, [0.5 + 0.5 * cos, 0.5 - 0.5 * sin], u_axis));
+    }
+
+    for sector in 0..sectors {
+        mesh.indices
+            .extend_from_slice(&[first, first + 1 + sector, first + 2 + sector]);
+    }
+}
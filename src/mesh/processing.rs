@@ -0,0 +1,580 @@
+use std::collections::HashMap;
+
+use glam::{Mat3, Mat4, Vec3, Vec4, Vec4Swizzles};
+
+use super::{Mesh, SubMesh, VertexData};
+use crate::bounds::{Aabb, BoundingSphere};
+
+// Taille du cache simulé pour l'optimisation de l'ordre des indices, les GPU
+// actuels n'ont plus vraiment de cache FIFO mais le gain reste le même
+const VERTEX_CACHE_SIZE: usize = 32;
+
+impl Mesh {
+    // Normales lissées, pondérées par l'aire des triangles
+    pub fn compute_smooth_normals(&mut self) {
+        let mut normals = vec![Vec3::ZERO; self.vertices.len()];
+
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| i as usize);
+            let normal = self.triangle_normal(a, b, c);
+
+            normals[a] += normal;
+            normals[b] += normal;
+            normals[c] += normal;
+        }
+
+        for (vertex, normal) in self.vertices.iter_mut().zip(normals) {
+            vertex.normal = normal.normalize_or_zero().into();
+        }
+    }
+
+    pub fn append(&mut self, other: &Mesh) {
+        let offset = self.vertices.len() as u32;
+
+        self.vertices.extend_from_slice(&other.vertices);
+        self.indices.extend(other.indices.iter().map(|index| index + offset));
+    }
+
+    // Ajoute `other` en gardant sa plage d'indices, pour le dessiner à part
+    pub fn append_sub_mesh(&mut self, other: &Mesh, material: u32) -> SubMesh {
+        let index_start = self.indices.len() as u32;
+        self.append(other);
+
+        SubMesh {
+            index_start,
+            index_count: other.indices.len() as u32,
+            base_vertex: 0,
+            material,
+        }
+    }
+
+    // Les normales passent par l'inverse transposée pour rester correctes avec
+    // une échelle non uniforme, et un miroir inverse le sens des triangles
+    pub fn transform(&mut self, matrix: Mat4) {
+        let normal_matrix = Mat3::from_mat4(matrix).inverse().transpose();
+
+        for vertex in &mut self.vertices {
+            vertex.position = matrix.transform_point3(vertex.position.into()).into();
+            vertex.normal = (normal_matrix * Vec3::from(vertex.normal)).normalize_or_zero().into();
+
+            let tangent = Vec4::from(vertex.tangent);
+            let tangent_xyz = matrix.transform_vector3(tangent.xyz()).normalize_or_zero();
+            vertex.tangent = tangent_xyz.extend(tangent.w).into();
+        }
+
+        if matrix.determinant() < 0.0 {
+            for triangle in self.indices.chunks_exact_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+    }
+
+    // Chaque triangle reçoit ses propres sommets, le mesh n'est donc plus partagé
+    pub fn compute_flat_normals(&mut self) {
+        let mut vertices = Vec::with_capacity(self.indices.len());
+
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| i as usize);
+            let normal = self.triangle_normal(a, b, c).normalize_or_zero().into();
+
+            for index in [a, b, c] {
+                vertices.push(VertexData {
+                    normal,
+                    ..self.vertices[index]
+                });
+            }
+        }
+
+        self.indices = (0..vertices.len() as u32).collect();
+        self.vertices = vertices;
+    }
+
+    // Tangentes MikkTSpace, les mêmes que celles des outils de baking. Demande
+    // des normales et des UV, renvoie false si le calcul a échoué. Un sommet
+    // partagé entre deux triangles garde la dernière tangente calculée.
+    pub fn compute_tangents(&mut self) -> bool {
+        bevy_mikktspace::generate_tangents(self)
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_points(self.vertices.iter().map(|vertex| Vec3::from(vertex.position)))
+    }
+
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        let positions: Vec<Vec3> = self.vertices.iter().map(|vertex| Vec3::from(vertex.position)).collect();
+        BoundingSphere::from_points(&positions)
+    }
+
+    // Fusionne les sommets dont tous les attributs sont égaux à `epsilon` près,
+    // deux sommets de part et d'autre d'un pas de quantification restent séparés.
+    // Un `epsilon` nul ou négatif ne fusionne que les sommets identiques.
+    pub fn weld(&mut self, epsilon: f32) {
+        let quantize = |value: f32| {
+            if epsilon > 0.0 {
+                (value / epsilon).round() as i64
+            } else {
+                // + 0.0 confond 0.0 et -0.0
+                i64::from((value + 0.0).to_bits())
+            }
+        };
+
+        let mut vertex_indices: HashMap<[i64; 12], u32> = HashMap::new();
+        let mut vertices = Vec::new();
+
+        let remap: Vec<u32> = self
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let attributes: &[f32; 12] = bytemuck::cast_ref(vertex);
+                let key = attributes.map(quantize);
+
+                *vertex_indices.entry(key).or_insert_with(|| {
+                    vertices.push(*vertex);
+                    vertices.len() as u32 - 1
+                })
+            })
+            .collect();
+
+        for index in &mut self.indices {
+            *index = remap[*index as usize];
+        }
+
+        self.vertices = vertices;
+    }
+
+    // Réordonne les triangles pour maximiser la réutilisation du cache de
+    // sommets (algorithme de Tom Forsyth), le rendu est identique
+    pub fn optimize_vertex_cache(&mut self) {
+        self.indices = optimize_indices(&self.indices);
+    }
+
+    // Même chose sous-mesh par sous-mesh, pour que leurs plages restent valides
+    pub fn optimize_sub_meshes_vertex_cache(&mut self, sub_meshes: &[SubMesh]) {
+        for sub_mesh in sub_meshes {
+            let range = sub_mesh.index_start as usize..(sub_mesh.index_start + sub_mesh.index_count) as usize;
+            let optimized = optimize_indices(&self.indices[range.clone()]);
+            self.indices[range].copy_from_slice(&optimized);
+        }
+    }
+
+    fn triangle_normal(&self, a: usize, b: usize, c: usize) -> Vec3 {
+        let pa = Vec3::from(self.vertices[a].position);
+        let pb = Vec3::from(self.vertices[b].position);
+        let pc = Vec3::from(self.vertices[c].position);
+
+        (pb - pa).cross(pc - pa)
+    }
+}
+
+fn optimize_indices(indices: &[u32]) -> Vec<u32> {
+    let triangle_count = indices.len() / 3;
+    let vertex_count = indices.iter().max().map_or(0, |&max| max as usize + 1);
+
+    if triangle_count == 0 {
+        return indices.to_vec();
+    }
+
+    // Liste des triangles de chaque sommet, à plat : les triangles du sommet
+    // v sont dans adjacency[offsets[v]..offsets[v] + remaining[v]]
+    let mut remaining = vec![0u32; vertex_count];
+    for &index in &indices[..triangle_count * 3] {
+        remaining[index as usize] += 1;
+    }
+
+    let mut offsets = vec![0usize; vertex_count];
+    for vertex in 1..vertex_count {
+        offsets[vertex] = offsets[vertex - 1] + remaining[vertex - 1] as usize;
+    }
+
+    let mut adjacency = vec![0u32; triangle_count * 3];
+    let mut filled = vec![0usize; vertex_count];
+    for (triangle, corners) in indices.chunks_exact(3).enumerate() {
+        for &index in corners {
+            let vertex = index as usize;
+            adjacency[offsets[vertex] + filled[vertex]] = triangle as u32;
+            filled[vertex] += 1;
+        }
+    }
+
+    let mut cache_positions: Vec<Option<usize>> = vec![None; vertex_count];
+    let mut vertex_scores: Vec<f32> = (0..vertex_count).map(|vertex| vertex_score(None, remaining[vertex])).collect();
+
+    let triangle_vertices = |triangle: usize| -> [usize; 3] {
+        let corners = &indices[triangle * 3..triangle * 3 + 3];
+        [corners[0], corners[1], corners[2]].map(|index| index as usize)
+    };
+
+    let mut emitted = vec![false; triangle_count];
+
+    let mut cache: Vec<usize> = Vec::with_capacity(VERTEX_CACHE_SIZE + 3);
+    let mut new_indices = Vec::with_capacity(triangle_count * 3);
+    let mut scan_cursor = 0;
+
+    let triangle_score = |triangle: usize, vertex_scores: &[f32]| -> f32 {
+        triangle_vertices(triangle).iter().map(|&vertex| vertex_scores[vertex]).sum()
+    };
+
+    let mut best_triangle = (0..triangle_count).max_by(|&a, &b| {
+        triangle_score(a, &vertex_scores).total_cmp(&triangle_score(b, &vertex_scores))
+    });
+
+    while let Some(triangle) = best_triangle {
+        emitted[triangle] = true;
+        let vertices = triangle_vertices(triangle);
+        new_indices.extend(vertices.iter().map(|&vertex| vertex as u32));
+
+        for &vertex in &vertices {
+            let start = offsets[vertex];
+            let count = remaining[vertex] as usize;
+            let slot = adjacency[start..start + count]
+                .iter()
+                .position(|&other| other as usize == triangle)
+                .unwrap();
+
+            adjacency.swap(start + slot, start + count - 1);
+            remaining[vertex] -= 1;
+        }
+
+        // Les sommets du triangle passent en tête du cache, les plus
+        // anciens en sortent mais doivent quand même être mis à jour
+        cache.retain(|vertex| !vertices.contains(vertex));
+        for &vertex in vertices.iter().rev() {
+            cache.insert(0, vertex);
+        }
+
+        for (position, &vertex) in cache.iter().enumerate() {
+            cache_positions[vertex] = (position < VERTEX_CACHE_SIZE).then_some(position);
+        }
+
+        for &vertex in &cache {
+            vertex_scores[vertex] = vertex_score(cache_positions[vertex], remaining[vertex]);
+        }
+
+        best_triangle = None;
+        let mut best_score = f32::NEG_INFINITY;
+
+        for &vertex in &cache {
+            let start = offsets[vertex];
+
+            for &other in &adjacency[start..start + remaining[vertex] as usize] {
+                let score = triangle_score(other as usize, &vertex_scores);
+
+                if score > best_score {
+                    best_score = score;
+                    best_triangle = Some(other as usize);
+                }
+            }
+        }
+
+        cache.truncate(VERTEX_CACHE_SIZE);
+
+        // Aucun voisin dans le cache : on repart du premier triangle restant
+        if best_triangle.is_none() {
+            while scan_cursor < triangle_count && emitted[scan_cursor] {
+                scan_cursor += 1;
+            }
+
+            best_triangle = (scan_cursor < triangle_count).then_some(scan_cursor);
+        }
+    }
+
+    // Un éventuel triangle incomplet en fin de liste est conservé
+    new_indices.extend_from_slice(&indices[triangle_count * 3..]);
+    new_indices
+}
+
+fn vertex_score(cache_position: Option<usize>, remaining: u32) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+
+    let cache_score = match cache_position {
+        // Les sommets du dernier triangle ont un score fixe pour ne pas
+        // favoriser les triangles qui les réutilisent tous les trois
+        Some(position) if position < 3 => 0.75,
+        Some(position) => {
+            let scaler = 1.0 / (VERTEX_CACHE_SIZE - 3) as f32;
+            (1.0 - (position - 3) as f32 * scaler).powf(1.5)
+        }
+        None => 0.0,
+    };
+
+    // Favorise les sommets qui n'ont plus que quelques triangles à émettre
+    cache_score + 2.0 * (remaining as f32).powf(-0.5)
+}
+
+impl bevy_mikktspace::Geometry for Mesh {
+    fn num_faces(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.vertices[self.indices[face * 3 + vert] as usize].position
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.vertices[self.indices[face * 3 + vert] as usize].normal
+    }
+
+    // MikkTSpace attend des UV avec l'origine en bas à gauche
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        let [u, v] = self.vertices[self.indices[face * 3 + vert] as usize].uv;
+        [u, 1.0 - v]
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        let index = self.indices[face * 3 + vert] as usize;
+        self.vertices[index].tangent = tangent;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec2, Vec3};
+
+    use super::*;
+    use crate::mesh::primitives;
+
+    fn vertex(position: [f32; 3], uv: [f32; 2]) -> VertexData {
+        VertexData {
+            uv,
+            ..VertexData::new(position, [0.0, 0.0, 0.0])
+        }
+    }
+
+    // Carré unité dans le plan XY, face vers +Z
+    fn quad(uv: impl Fn(f32, f32) -> [f32; 2]) -> Mesh {
+        let corners = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+
+        Mesh {
+            vertices: corners.iter().map(|&[x, y]| vertex([x, y, 0.0], uv(x, y))).collect(),
+            indices: vec![0, 1, 2, 0, 2, 3],
+        }
+    }
+
+    // Deux triangles de même aire pliés le long de l'axe X
+    fn roof() -> Mesh {
+        Mesh {
+            vertices: vec![
+                vertex([0.0, 0.0, 0.0], [0.0, 0.0]),
+                vertex([1.0, 0.0, 0.0], [0.0, 0.0]),
+                vertex([0.0, 1.0, -1.0], [0.0, 0.0]),
+                vertex([0.0, -1.0, -1.0], [0.0, 0.0]),
+            ],
+            indices: vec![0, 1, 2, 0, 3, 1],
+        }
+    }
+
+    fn assert_close(a: impl Into<Vec3>, b: impl Into<Vec3>) {
+        let (a, b) = (a.into(), b.into());
+        assert!(a.abs_diff_eq(b, 1e-5), "{a} != {b}");
+    }
+
+    // Triangles sous une forme canonique, pour comparer deux listes d'indices
+    // sans tenir compte de l'ordre des triangles ni du sommet de départ
+    fn sorted_triangles(indices: &[u32]) -> Vec<[u32; 3]> {
+        let mut triangles: Vec<[u32; 3]> = indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let start = (0..3).min_by_key(|&i| triangle[i]).unwrap();
+                [0, 1, 2].map(|i| triangle[(start + i) % 3])
+            })
+            .collect();
+
+        triangles.sort();
+        triangles
+    }
+
+    #[test]
+    fn smooth_normals_of_a_quad() {
+        let mut mesh = quad(|_, _| [0.0, 0.0]);
+        mesh.compute_smooth_normals();
+
+        for vertex in &mesh.vertices {
+            assert_close(vertex.normal, Vec3::Z);
+        }
+    }
+
+    #[test]
+    fn smooth_normals_average_adjacent_faces() {
+        let mut mesh = roof();
+        mesh.compute_smooth_normals();
+
+        let left = Vec3::new(0.0, 1.0, 1.0).normalize();
+        let right = Vec3::new(0.0, -1.0, 1.0).normalize();
+
+        assert_close(mesh.vertices[0].normal, Vec3::Z);
+        assert_close(mesh.vertices[1].normal, Vec3::Z);
+        assert_close(mesh.vertices[2].normal, left);
+        assert_close(mesh.vertices[3].normal, right);
+    }
+
+    #[test]
+    fn smooth_normals_of_a_cube_with_split_faces() {
+        let expected = primitives::cube(Vec3::ONE, 2);
+        let mut mesh = expected.clone();
+        mesh.compute_smooth_normals();
+
+        for (vertex, expected) in mesh.vertices.iter().zip(&expected.vertices) {
+            assert_close(vertex.normal, expected.normal);
+        }
+    }
+
+    #[test]
+    fn flat_normals() {
+        let mut mesh = roof();
+        mesh.compute_flat_normals();
+
+        assert_eq!(mesh.vertices.len(), 6);
+        assert_eq!(mesh.indices, [0, 1, 2, 3, 4, 5]);
+
+        for vertex in &mesh.vertices[..3] {
+            assert_close(vertex.normal, Vec3::new(0.0, 1.0, 1.0).normalize());
+        }
+        for vertex in &mesh.vertices[3..] {
+            assert_close(vertex.normal, Vec3::new(0.0, -1.0, 1.0).normalize());
+        }
+    }
+
+    #[test]
+    fn tangents_follow_u() {
+        let mut mesh = quad(|x, y| [x, 1.0 - y]);
+        mesh.compute_smooth_normals();
+        assert!(mesh.compute_tangents());
+
+        for vertex in &mesh.vertices {
+            assert_close(Vec4::from(vertex.tangent).xyz(), Vec3::X);
+            assert_eq!(vertex.tangent[3], 1.0);
+        }
+    }
+
+    #[test]
+    fn mirrored_uvs_flip_handedness() {
+        let mut mesh = quad(|x, y| [1.0 - x, 1.0 - y]);
+        mesh.compute_smooth_normals();
+        assert!(mesh.compute_tangents());
+
+        for vertex in &mesh.vertices {
+            assert_close(Vec4::from(vertex.tangent).xyz(), Vec3::NEG_X);
+            assert_eq!(vertex.tangent[3], -1.0);
+        }
+    }
+
+    #[test]
+    fn tangents_of_a_sphere_are_unit_and_orthogonal() {
+        let mut mesh = primitives::uv_sphere(1.0, 16, 8);
+        assert!(mesh.compute_tangents());
+
+        for vertex in &mesh.vertices {
+            let tangent = Vec4::from(vertex.tangent);
+            assert_eq!(tangent.w.abs(), 1.0);
+
+            // Les pôles dégénérés peuvent n'avoir aucune tangente
+            if tangent.xyz() != Vec3::ZERO {
+                assert!((tangent.xyz().length() - 1.0).abs() < 1e-3);
+                assert!(tangent.xyz().dot(Vec3::from(vertex.normal)).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn weld_merges_duplicates() {
+        let expected = primitives::cube(Vec3::ONE, 1);
+        let mut mesh = expected.clone();
+        mesh.compute_flat_normals();
+        assert_eq!(mesh.vertices.len(), 36);
+
+        // Un écart bien plus petit que epsilon disparaît aussi
+        mesh.vertices[0].position[0] += 1e-6;
+
+        let before: Vec<VertexData> = mesh.indices.iter().map(|&index| mesh.vertices[index as usize]).collect();
+        mesh.weld(1e-3);
+
+        // Les sommets des deux triangles d'une face sont communs, pas ceux de deux faces
+        assert_eq!(mesh.vertices.len(), 24);
+        assert_eq!(mesh.indices.len(), 36);
+
+        for (&index, before) in mesh.indices.iter().zip(&before) {
+            let vertex = mesh.vertices[index as usize];
+            assert_close(vertex.position, before.position);
+            assert_eq!(vertex.normal, before.normal);
+            assert_eq!(vertex.uv, before.uv);
+        }
+    }
+
+    #[test]
+    fn weld_with_zero_epsilon_is_exact() {
+        let mut mesh = quad(|x, y| [x, y]);
+        let duplicate = mesh.vertices[0];
+        mesh.vertices.push(duplicate);
+        mesh.vertices[4].position[2] = -0.0;
+
+        let mut nearly = mesh.vertices[1];
+        nearly.uv[0] += 1e-6;
+        mesh.vertices.push(nearly);
+        mesh.indices.extend_from_slice(&[4, 5, 2]);
+
+        for epsilon in [0.0, -1.0] {
+            let mut welded = mesh.clone();
+            welded.weld(epsilon);
+
+            // Seul le double exact du premier sommet disparaît
+            assert_eq!(welded.vertices.len(), 5, "{epsilon}");
+            assert_eq!(&welded.indices[6..], [0, 4, 2]);
+        }
+    }
+
+    #[test]
+    fn optimize_vertex_cache_keeps_triangles() {
+        let mut mesh = primitives::plane(Vec2::ONE, 16);
+
+        // Triangles mélangés pour que l'ordre de départ soit mauvais
+        let mut triangles: Vec<[u32; 3]> = mesh.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+        let mut seed = 1u32;
+        for i in (1..triangles.len()).rev() {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            triangles.swap(i, seed as usize % (i + 1));
+        }
+        mesh.indices = triangles.concat();
+
+        let expected = sorted_triangles(&mesh.indices);
+        mesh.optimize_vertex_cache();
+
+        assert_eq!(mesh.indices.len(), expected.len() * 3);
+        assert_eq!(sorted_triangles(&mesh.indices), expected);
+    }
+
+    #[test]
+    fn optimize_sub_meshes_keeps_ranges() {
+        let mut mesh = primitives::cube(Vec3::ONE, 2);
+        let half = mesh.indices.len() as u32 / 2;
+        let sub_meshes = [
+            SubMesh {
+                index_start: 0,
+                index_count: half,
+                ..Default::default()
+            },
+            SubMesh {
+                index_start: half,
+                index_count: half,
+                ..Default::default()
+            },
+        ];
+
+        let expected: Vec<_> = sub_meshes
+            .iter()
+            .map(|sub_mesh| sorted_triangles(&mesh.indices[sub_mesh.index_range().start as usize..][..half as usize]))
+            .collect();
+
+        mesh.optimize_sub_meshes_vertex_cache(&sub_meshes);
+
+        for (sub_mesh, expected) in sub_meshes.iter().zip(expected) {
+            let range = sub_mesh.index_range();
+            assert_eq!(sorted_triangles(&mesh.indices[range.start as usize..range.end as usize]), expected);
+        }
+    }
+}
@@ -0,0 +1,551 @@
+use glam::{Vec3, Vec4};
+use wgpu::include_spirv;
+
+use crate::camera::{CameraUniform, DepthMode};
+
+const WORKGROUP_SIZE: u32 = 64;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EmitterShape {
+    Point,
+    // Dans tout le volume de la sphère
+    Sphere { radius: f32 },
+    Box { half_extents: Vec3 },
+}
+
+// Paramètres des particules émises. La couleur et la taille sont
+// interpolées de `start` à `end` au cours de la vie de chaque particule.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParticleEmitter {
+    pub position: Vec3,
+    pub shape: EmitterShape,
+    // Particules par seconde
+    pub rate: f32,
+    // Tirée entre les deux bornes pour chaque particule
+    pub lifetime: (f32, f32),
+    pub velocity: Vec3,
+    // Vecteur aléatoire de cette taille maximale ajouté à `velocity`
+    pub velocity_randomness: f32,
+    pub acceleration: Vec3,
+    // Fraction de la vitesse perdue par seconde
+    pub drag: f32,
+    pub size: (f32, f32),
+    pub color: (Vec4, Vec4),
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self {
+            position: Vec3::ZERO,
+            shape: EmitterShape::Point,
+            rate: 50.0,
+            lifetime: (1.0, 2.0),
+            velocity: Vec3::Y,
+            velocity_randomness: 0.5,
+            acceleration: Vec3::ZERO,
+            drag: 0.0,
+            size: (0.1, 0.1),
+            color: (Vec4::ONE, Vec4::new(1.0, 1.0, 1.0, 0.0)),
+        }
+    }
+}
+
+impl ParticleEmitter {
+    pub fn with_shape(mut self, shape: EmitterShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    pub fn with_rate(mut self, rate: f32) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_lifetime(mut self, min: f32, max: f32) -> Self {
+        self.lifetime = (min, max);
+        self
+    }
+
+    pub fn with_velocity(mut self, velocity: Vec3, randomness: f32) -> Self {
+        self.velocity = velocity;
+        self.velocity_randomness = randomness;
+        self
+    }
+
+    pub fn with_acceleration(mut self, acceleration: Vec3) -> Self {
+        self.acceleration = acceleration;
+        self
+    }
+
+    pub fn with_drag(mut self, drag: f32) -> Self {
+        self.drag = drag;
+        self
+    }
+
+    pub fn with_size(mut self, start: f32, end: f32) -> Self {
+        self.size = (start, end);
+        self
+    }
+
+    pub fn with_color(mut self, start: Vec4, end: Vec4) -> Self {
+        self.color = (start, end);
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParticleBackend {
+    // Émission et simulation dans un compute shader
+    Gpu,
+    // Simulées sur le CPU puis copiées dans le buffer de sommets
+    Cpu,
+}
+
+impl ParticleBackend {
+    // WebGL2 n'a ni compute shaders ni storage buffers
+    pub fn detect(device: &wgpu::Device) -> Self {
+        let limits = device.limits();
+
+        if limits.max_compute_workgroups_per_dimension > 0 && limits.max_storage_buffers_per_shader_stage > 0 {
+            Self::Gpu
+        } else {
+            Self::Cpu
+        }
+    }
+}
+
+// Doit correspondre à Particle dans particle_common.nzsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct Particle {
+    position: [f32; 3],
+    age: f32,
+    velocity: [f32; 3],
+    lifetime: f32,
+}
+
+// Doit correspondre à ParticleParams dans particle_common.nzsl
+#[repr(C)]
+#[derive(Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct ParticleParamsUniform {
+    view_proj_matrix: [f32; 16],
+    camera_pos: [f32; 3],
+    capacity: u32,
+    emitter_pos: [f32; 3],
+    shape: u32,
+    shape_size: [f32; 3],
+    spawn_start: u32,
+    velocity: [f32; 3],
+    spawn_count: u32,
+    acceleration: [f32; 3],
+    seed: u32,
+    lifetime_min: f32,
+    lifetime_max: f32,
+    velocity_randomness: f32,
+    drag: f32,
+    size_start: f32,
+    size_end: f32,
+    dt: f32,
+    _padding: f32,
+    color_start: [f32; 4],
+    color_end: [f32; 4],
+}
+
+// Système de particules à capacité fixe, les nouvelles particules
+// remplacent les plus anciennes. `update` puis `simulate` avant la passe où
+// `draw` est appelé.
+pub struct ParticleSystem {
+    pub emitter: ParticleEmitter,
+
+    backend: ParticleBackend,
+    capacity: u32,
+    // Prochain emplacement à réutiliser
+    cursor: u32,
+    spawn_accumulator: f32,
+    frame: u32,
+    params: ParticleParamsUniform,
+    params_buffer: wgpu::Buffer,
+    particle_buffer: wgpu::Buffer,
+    // Vide avec le backend GPU
+    cpu_particles: Vec<Particle>,
+    compute: Option<(wgpu::ComputePipeline, wgpu::BindGroup)>,
+    color_format: wgpu::TextureFormat,
+    depth_mode: DepthMode,
+    render_pipeline_layout: wgpu::PipelineLayout,
+    render_pipeline: wgpu::RenderPipeline,
+    render_bind_group: wgpu::BindGroup,
+}
+
+impl ParticleSystem {
+    // Les particules sont dessinées dans une passe avec une cible
+    // `color_format` et une profondeur en Depth32Float, sans écrire la profondeur
+    pub fn new(
+        device: &wgpu::Device,
+        emitter: ParticleEmitter,
+        capacity: u32,
+        color_format: wgpu::TextureFormat,
+        depth_mode: DepthMode,
+    ) -> Self {
+        let backend = ParticleBackend::detect(device);
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particle Params Buffer"),
+            size: std::mem::size_of::<ParticleParamsUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let particle_usage = match backend {
+            ParticleBackend::Gpu => wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX,
+            ParticleBackend::Cpu => wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        };
+
+        // Tout à zéro : un âge égal à la durée de vie, les particules sont mortes
+        let particle_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particle Buffer"),
+            size: (capacity as usize * std::mem::size_of::<Particle>()) as wgpu::BufferAddress,
+            usage: particle_usage,
+            mapped_at_creation: false,
+        });
+
+        let compute = (backend == ParticleBackend::Gpu)
+            .then(|| create_compute_pipeline(device, &params_buffer, &particle_buffer));
+
+        let render_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Particle Render Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let render_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Particle Render Bind Group"),
+            layout: &render_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buffer.as_entire_binding(),
+            }],
+        });
+
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Particle Render Pipeline Layout"),
+            bind_group_layouts: &[&render_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let render_pipeline = create_render_pipeline(device, &render_pipeline_layout, color_format, depth_mode);
+
+        Self {
+            emitter,
+            backend,
+            capacity,
+            cursor: 0,
+            spawn_accumulator: 0.0,
+            frame: 0,
+            params: ParticleParamsUniform::default(),
+            params_buffer,
+            particle_buffer,
+            cpu_particles: match backend {
+                ParticleBackend::Gpu => Vec::new(),
+                ParticleBackend::Cpu => vec![Particle::default(); capacity as usize],
+            },
+            compute,
+            color_format,
+            depth_mode,
+            render_pipeline_layout,
+            render_pipeline,
+            render_bind_group,
+        }
+    }
+
+    pub fn backend(&self) -> ParticleBackend {
+        self.backend
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    // Le test de profondeur est fixé dans le pipeline
+    pub fn set_depth_mode(&mut self, device: &wgpu::Device, depth_mode: DepthMode) {
+        if depth_mode != self.depth_mode {
+            self.depth_mode = depth_mode;
+            self.render_pipeline =
+                create_render_pipeline(device, &self.render_pipeline_layout, self.color_format, depth_mode);
+        }
+    }
+
+    // Décide des particules à émettre pendant `dt`. Avec le backend CPU, elles
+    // sont aussi simulées et envoyées au GPU.
+    pub fn update(&mut self, queue: &wgpu::Queue, camera: &CameraUniform, dt: f32) {
+        let emitter = &self.emitter;
+
+        self.spawn_accumulator += emitter.rate * dt;
+        let spawn_count = (self.spawn_accumulator as u32).min(self.capacity);
+        self.spawn_accumulator -= spawn_count as f32;
+        // Au-delà de la capacité, le surplus est perdu
+        self.spawn_accumulator = self.spawn_accumulator.min(self.capacity as f32);
+
+        let (shape, shape_size) = match emitter.shape {
+            EmitterShape::Point => (0, Vec3::ZERO),
+            EmitterShape::Sphere { radius } => (1, Vec3::splat(radius)),
+            EmitterShape::Box { half_extents } => (2, half_extents),
+        };
+
+        self.params = ParticleParamsUniform {
+            view_proj_matrix: camera.view_proj_matrix,
+            camera_pos: camera.pos,
+            capacity: self.capacity,
+            emitter_pos: emitter.position.into(),
+            shape,
+            shape_size: shape_size.into(),
+            spawn_start: self.cursor,
+            velocity: emitter.velocity.into(),
+            spawn_count,
+            acceleration: emitter.acceleration.into(),
+            seed: self.frame,
+            lifetime_min: emitter.lifetime.0,
+            lifetime_max: emitter.lifetime.1,
+            velocity_randomness: emitter.velocity_randomness,
+            drag: emitter.drag,
+            size_start: emitter.size.0,
+            size_end: emitter.size.1,
+            dt,
+            _padding: 0.0,
+            color_start: emitter.color.0.into(),
+            color_end: emitter.color.1.into(),
+        };
+
+        self.cursor = (self.cursor + spawn_count) % self.capacity.max(1);
+        self.frame = self.frame.wrapping_add(1);
+
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&[self.params]));
+
+        if self.backend == ParticleBackend::Cpu {
+            for (index, particle) in self.cpu_particles.iter_mut().enumerate() {
+                simulate_particle(&self.params, index as u32, particle);
+            }
+
+            queue.write_buffer(&self.particle_buffer, 0, bytemuck::cast_slice(&self.cpu_particles));
+        }
+    }
+
+    // Ne fait rien avec le backend CPU
+    pub fn simulate(&self, encoder: &mut wgpu::CommandEncoder) {
+        let Some((pipeline, bind_group)) = &self.compute else {
+            return;
+        };
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Particle Simulation Pass"),
+        });
+
+        compute_pass.set_pipeline(pipeline);
+        compute_pass.set_bind_group(0, bind_group, &[]);
+        compute_pass.dispatch_workgroups(self.capacity.div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+
+    // Six sommets par particule, les mortes sont rejetées dans le vertex shader
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.render_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.particle_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.capacity);
+    }
+}
+
+fn create_compute_pipeline(
+    device: &wgpu::Device,
+    params_buffer: &wgpu::Buffer,
+    particle_buffer: &wgpu::Buffer,
+) -> (wgpu::ComputePipeline, wgpu::BindGroup) {
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Particle Simulation Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+    });
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Particle Simulation Bind Group"),
+        layout: &bind_group_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: particle_buffer.as_entire_binding(),
+            },
+        ],
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Particle Simulation Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let shader = device.create_shader_module(include_spirv!(concat!(env!("OUT_DIR"), "/particle_simulate.spv")));
+
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Particle Simulation Pipeline"),
+        layout: Some(&pipeline_layout),
+        module: &shader,
+        entry_point: "main",
+    });
+
+    (pipeline, bind_group)
+}
+
+fn create_render_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    color_format: wgpu::TextureFormat,
+    depth_mode: DepthMode,
+) -> wgpu::RenderPipeline {
+    let shader = device.create_shader_module(include_spirv!(concat!(env!("OUT_DIR"), "/particle.spv")));
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Particle Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<Particle>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &[
+                    wgpu::VertexAttribute {
+                        offset: 0,
+                        shader_location: 0,
+                        format: wgpu::VertexFormat::Float32x4,
+                    },
+                    wgpu::VertexAttribute {
+                        offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                        shader_location: 1,
+                        format: wgpu::VertexFormat::Float32x4,
+                    },
+                ],
+            }],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: color_format,
+                // Additif, la couleur sortie par le shader est prémultipliée
+                blend: Some(wgpu::BlendState {
+                    color: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::One,
+                        dst_factor: wgpu::BlendFactor::One,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                    alpha: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::Zero,
+                        dst_factor: wgpu::BlendFactor::One,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                }),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            // Les quads font face à la caméra, l'ordre des sommets n'a pas d'importance
+            cull_mode: None,
+            ..Default::default()
+        },
+        // Cachées par la scène mais sans se cacher entre elles
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth32Float,
+            depth_write_enabled: false,
+            depth_compare: depth_mode.compare_function(),
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    })
+}
+
+// Hash de Wang, le même que dans particle_simulate.nzsl
+fn hash(value: u32) -> u32 {
+    let mut seed = (value ^ 61) ^ (value >> 16);
+    seed = seed.wrapping_mul(9);
+    seed ^= seed >> 4;
+    seed = seed.wrapping_mul(668265261);
+    seed ^ (seed >> 15)
+}
+
+fn random(seed: u32) -> f32 {
+    (hash(seed) & 0xFFFFFF) as f32 / 16777215.0
+}
+
+fn random_vector(seed: u32) -> Vec3 {
+    Vec3::new(random(seed), random(seed.wrapping_add(1)), random(seed.wrapping_add(2))) * 2.0 - Vec3::ONE
+}
+
+// Même calcul que le compute shader, pour une particule
+fn simulate_particle(params: &ParticleParamsUniform, index: u32, particle: &mut Particle) {
+    let capacity = params.capacity;
+    let spawn_offset = (index + capacity - params.spawn_start) % capacity;
+
+    if spawn_offset < params.spawn_count {
+        let seed = hash(index ^ hash(params.seed)).wrapping_mul(8);
+
+        let shape_size = Vec3::from(params.shape_size);
+        let offset = match params.shape {
+            1 => (random_vector(seed) + Vec3::new(0.0001, 0.0, 0.0)).normalize()
+                * shape_size.x
+                * random(seed.wrapping_add(3)),
+            2 => random_vector(seed) * shape_size,
+            _ => Vec3::ZERO,
+        };
+
+        let velocity = Vec3::from(params.velocity) + random_vector(seed.wrapping_add(4)) * params.velocity_randomness;
+
+        *particle = Particle {
+            position: (Vec3::from(params.emitter_pos) + offset).into(),
+            age: 0.0,
+            velocity: velocity.into(),
+            lifetime: params.lifetime_min
+                + (params.lifetime_max - params.lifetime_min) * random(seed.wrapping_add(7)),
+        };
+    } else if particle.age < particle.lifetime {
+        let dt = params.dt;
+        let velocity = (Vec3::from(particle.velocity) + Vec3::from(params.acceleration) * dt)
+            * (1.0 - params.drag * dt).max(0.0);
+
+        particle.position = (Vec3::from(particle.position) + velocity * dt).into();
+        particle.velocity = velocity.into();
+        particle.age += dt;
+    }
+}
@@ -0,0 +1,579 @@
+use glam::{Mat3, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+use self::collision::{ContactPoint, ShapePose};
+use crate::{
+    ecs::{hierarchy::world_matrix, Entity, World},
+    transform::Transform,
+};
+
+mod collision;
+
+// Au-delà, le retard est abandonné plutôt que de ralentir encore la frame
+const MAX_STEPS_PER_FRAME: u32 = 4;
+// Pénétration tolérée, pour que les contacts au repos ne disparaissent pas
+// d'un pas à l'autre
+const PENETRATION_SLOP: f32 = 0.01;
+// Fraction de la pénétration corrigée à chaque pas
+const BAUMGARTE: f32 = 0.2;
+// En dessous de cette vitesse d'impact, pas de rebond, sinon les corps au
+// repos tremblent
+const RESTITUTION_THRESHOLD: f32 = 1.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BodyKind {
+    Dynamic,
+    // Déplacé uniquement par sa vitesse, les contacts ne l'arrêtent pas
+    Kinematic,
+    Static,
+}
+
+// Un corps dynamique ou cinématique lit et écrit directement son `Transform`,
+// il ne doit donc pas avoir de `Parent`. Un corps statique peut en avoir un.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RigidBody {
+    pub kind: BodyKind,
+    pub mass: f32,
+    pub linear_velocity: Vec3,
+    // Radians par seconde autour de chaque axe du monde
+    pub angular_velocity: Vec3,
+    pub restitution: f32,
+    pub friction: f32,
+    // Fraction de la vitesse perdue par seconde
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+}
+
+impl Default for RigidBody {
+    fn default() -> Self {
+        Self::dynamic(1.0)
+    }
+}
+
+impl RigidBody {
+    pub fn dynamic(mass: f32) -> Self {
+        Self {
+            kind: BodyKind::Dynamic,
+            mass,
+            linear_velocity: Vec3::ZERO,
+            angular_velocity: Vec3::ZERO,
+            restitution: 0.3,
+            friction: 0.5,
+            linear_damping: 0.01,
+            angular_damping: 0.05,
+        }
+    }
+
+    pub fn kinematic() -> Self {
+        Self {
+            kind: BodyKind::Kinematic,
+            ..Self::dynamic(0.0)
+        }
+    }
+
+    pub fn fixed() -> Self {
+        Self {
+            kind: BodyKind::Static,
+            ..Self::dynamic(0.0)
+        }
+    }
+
+    pub fn with_velocity(mut self, velocity: Vec3) -> Self {
+        self.linear_velocity = velocity;
+        self
+    }
+
+    pub fn with_angular_velocity(mut self, angular_velocity: Vec3) -> Self {
+        self.angular_velocity = angular_velocity;
+        self
+    }
+
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = restitution;
+        self
+    }
+
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    pub fn with_damping(mut self, linear: f32, angular: f32) -> Self {
+        self.linear_damping = linear;
+        self.angular_damping = angular;
+        self
+    }
+}
+
+// Dans l'espace local de l'entité, multipliée par l'échelle de son `Transform`.
+// Une entité avec une forme mais sans `RigidBody` est un obstacle statique.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CollisionShape {
+    Box { half_extents: Vec3 },
+    Sphere { radius: f32 },
+    // Alignée sur l'axe Y, `half_height` sans compter les demi-sphères
+    Capsule { radius: f32, half_height: f32 },
+}
+
+impl CollisionShape {
+    fn scaled(self, scale: Vec3) -> Self {
+        match self {
+            Self::Box { half_extents } => Self::Box {
+                half_extents: half_extents * scale,
+            },
+            Self::Sphere { radius } => Self::Sphere {
+                radius: radius * scale.max_element(),
+            },
+            Self::Capsule { radius, half_height } => Self::Capsule {
+                radius: radius * scale.x.max(scale.z),
+                half_height: half_height * scale.y,
+            },
+        }
+    }
+
+    // Moments d'inertie sur les axes locaux
+    fn inertia(&self, mass: f32) -> Vec3 {
+        match *self {
+            Self::Box { half_extents } => {
+                let size = half_extents * 2.0;
+                let squared = size * size;
+                Vec3::new(squared.y + squared.z, squared.x + squared.z, squared.x + squared.y) * mass / 12.0
+            }
+            Self::Sphere { radius } => Vec3::splat(0.4 * mass * radius * radius),
+            // Approchée par un cylindre de même longueur totale
+            Self::Capsule { radius, half_height } => {
+                let length = 2.0 * (half_height + radius);
+                let side = mass * (3.0 * radius * radius + length * length) / 12.0;
+                Vec3::new(side, 0.5 * mass * radius * radius, side)
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Contact {
+    pub a: Entity,
+    pub b: Entity,
+    pub point: Vec3,
+    // De `a` vers `b`
+    pub normal: Vec3,
+    pub depth: f32,
+}
+
+// Simulation à pas fixe : `step` accumule le temps de la frame et avance
+// d'autant de pas entiers que possible
+#[derive(Clone, Debug)]
+pub struct Physics {
+    pub gravity: Vec3,
+    pub time_step: f32,
+    // Passes du solveur par pas, plus il y en a plus les piles sont stables
+    pub iterations: u32,
+    accumulator: f32,
+    contacts: Vec<Contact>,
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// État d'un corps pendant un pas, recopié dans le monde à la fin
+struct Body {
+    entity: Entity,
+    kind: BodyKind,
+    position: Vec3,
+    rotation: Quat,
+    shape: Option<CollisionShape>,
+    linear_velocity: Vec3,
+    angular_velocity: Vec3,
+    inverse_mass: f32,
+    inverse_inertia: Mat3,
+    restitution: f32,
+    friction: f32,
+    linear_damping: f32,
+    angular_damping: f32,
+}
+
+impl Body {
+    fn velocity_at(&self, point: Vec3) -> Vec3 {
+        self.linear_velocity + self.angular_velocity.cross(point - self.position)
+    }
+
+    fn apply_impulse(&mut self, impulse: Vec3, point: Vec3) {
+        self.linear_velocity += impulse * self.inverse_mass;
+        self.angular_velocity += self.inverse_inertia * (point - self.position).cross(impulse);
+    }
+
+    fn pose(&self) -> Option<ShapePose> {
+        Some(ShapePose {
+            shape: self.shape?,
+            position: self.position,
+            rotation: self.rotation,
+        })
+    }
+}
+
+// Contact entre deux corps, avec ce que le solveur précalcule
+struct ContactConstraint {
+    a: usize,
+    b: usize,
+    point: ContactPoint,
+    tangents: [Vec3; 2],
+    normal_mass: f32,
+    tangent_masses: [f32; 2],
+    bias: f32,
+    friction: f32,
+    normal_impulse: f32,
+    tangent_impulses: [f32; 2],
+}
+
+impl Physics {
+    pub fn new() -> Self {
+        Self {
+            gravity: Vec3::new(0.0, -9.81, 0.0),
+            time_step: 1.0 / 60.0,
+            iterations: 8,
+            accumulator: 0.0,
+            contacts: Vec::new(),
+        }
+    }
+
+    pub fn with_gravity(mut self, gravity: Vec3) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub fn with_time_step(mut self, time_step: f32) -> Self {
+        self.time_step = time_step;
+        self
+    }
+
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    // Contacts du dernier pas
+    pub fn contacts(&self) -> &[Contact] {
+        &self.contacts
+    }
+
+    // Renvoie le nombre de pas effectués
+    pub fn step(&mut self, world: &mut World, dt: f32) -> u32 {
+        self.accumulator += dt;
+
+        let mut steps = 0;
+        while self.accumulator >= self.time_step && steps < MAX_STEPS_PER_FRAME {
+            self.accumulator -= self.time_step;
+            self.fixed_step(world);
+            steps += 1;
+        }
+
+        if steps == MAX_STEPS_PER_FRAME {
+            self.accumulator = self.accumulator.min(self.time_step);
+        }
+
+        steps
+    }
+
+    fn fixed_step(&mut self, world: &mut World) {
+        let dt = self.time_step;
+        let mut bodies = gather_bodies(world);
+
+        for body in bodies.iter_mut().filter(|body| body.kind == BodyKind::Dynamic) {
+            body.linear_velocity += self.gravity * dt;
+            body.linear_velocity *= (1.0 - body.linear_damping * dt).max(0.0);
+            body.angular_velocity *= (1.0 - body.angular_damping * dt).max(0.0);
+        }
+
+        let mut constraints = find_contacts(&bodies, dt);
+
+        for _ in 0..self.iterations {
+            for constraint in &mut constraints {
+                solve_contact(&mut bodies, constraint);
+            }
+        }
+
+        for body in bodies.iter_mut().filter(|body| body.kind != BodyKind::Static) {
+            body.position += body.linear_velocity * dt;
+            body.rotation = (Quat::from_scaled_axis(body.angular_velocity * dt) * body.rotation).normalize();
+        }
+
+        self.contacts = constraints
+            .iter()
+            .map(|constraint| Contact {
+                a: bodies[constraint.a].entity,
+                b: bodies[constraint.b].entity,
+                point: constraint.point.point,
+                normal: constraint.point.normal,
+                depth: constraint.point.depth,
+            })
+            .collect();
+
+        // Les corps statiques n'ont pas bougé, seuls les autres sont recopiés
+        for body in bodies.iter().filter(|body| body.kind != BodyKind::Static) {
+            if let Some(transform) = world.get_mut::<Transform>(body.entity) {
+                transform.translation = body.position;
+                transform.rotation = body.rotation;
+            }
+
+            if let Some(rigid_body) = world.get_mut::<RigidBody>(body.entity) {
+                rigid_body.linear_velocity = body.linear_velocity;
+                rigid_body.angular_velocity = body.angular_velocity;
+            }
+        }
+    }
+}
+
+fn gather_bodies(world: &World) -> Vec<Body> {
+    let mut bodies = Vec::new();
+
+    world.query::<(&Transform, Option<&RigidBody>, Option<&CollisionShape>)>(|entity, (transform, rigid_body, shape)| {
+        if rigid_body.is_none() && shape.is_none() {
+            return;
+        }
+
+        let rigid_body = rigid_body.copied().unwrap_or_else(RigidBody::fixed);
+
+        let (scale, rotation, position) = match rigid_body.kind {
+            BodyKind::Static => match world_matrix(world, entity) {
+                Some(matrix) => matrix.to_scale_rotation_translation(),
+                None => return,
+            },
+            _ => (transform.scale, transform.rotation, transform.translation),
+        };
+
+        let shape = shape.map(|shape| shape.scaled(scale));
+        let (inverse_mass, inverse_inertia_local) = match (rigid_body.kind, shape) {
+            (BodyKind::Dynamic, _) if rigid_body.mass > 0.0 => {
+                let inertia = shape.map_or(Vec3::ONE * rigid_body.mass, |shape| shape.inertia(rigid_body.mass));
+                (1.0 / rigid_body.mass, inertia.recip())
+            }
+            _ => (0.0, Vec3::ZERO),
+        };
+
+        let rotation_matrix = Mat3::from_quat(rotation);
+
+        bodies.push(Body {
+            entity,
+            kind: rigid_body.kind,
+            position,
+            rotation,
+            shape,
+            linear_velocity: rigid_body.linear_velocity,
+            angular_velocity: rigid_body.angular_velocity,
+            inverse_mass,
+            inverse_inertia: rotation_matrix * Mat3::from_diagonal(inverse_inertia_local) * rotation_matrix.transpose(),
+            restitution: rigid_body.restitution,
+            friction: rigid_body.friction,
+            linear_damping: rigid_body.linear_damping,
+            angular_damping: rigid_body.angular_damping,
+        });
+    });
+
+    bodies
+}
+
+// Toutes les paires sont testées, ce qui suffit pour quelques dizaines de corps
+fn find_contacts(bodies: &[Body], dt: f32) -> Vec<ContactConstraint> {
+    let poses: Vec<_> = bodies.iter().map(|body| body.pose().map(|pose| (pose, pose.aabb()))).collect();
+    let mut points = Vec::new();
+    let mut constraints = Vec::new();
+
+    for a in 0..bodies.len() {
+        for b in a + 1..bodies.len() {
+            if bodies[a].kind != BodyKind::Dynamic && bodies[b].kind != BodyKind::Dynamic {
+                continue;
+            }
+
+            let (Some((a_pose, a_aabb)), Some((b_pose, b_aabb))) = (&poses[a], &poses[b]) else {
+                continue;
+            };
+
+            if !a_aabb.intersects(b_aabb) {
+                continue;
+            }
+
+            points.clear();
+            collision::collide(a_pose, b_pose, &mut points);
+
+            for point in &points {
+                constraints.push(prepare_contact(bodies, a, b, *point, dt));
+            }
+        }
+    }
+
+    constraints
+}
+
+fn prepare_contact(bodies: &[Body], a: usize, b: usize, point: ContactPoint, dt: f32) -> ContactConstraint {
+    let (body_a, body_b) = (&bodies[a], &bodies[b]);
+    let normal = point.normal;
+    let (tangent, bitangent) = normal.any_orthonormal_pair();
+
+    let effective_mass = |direction: Vec3| {
+        let r_a = point.point - body_a.position;
+        let r_b = point.point - body_b.position;
+        let angular_a = (body_a.inverse_inertia * r_a.cross(direction)).cross(r_a);
+        let angular_b = (body_b.inverse_inertia * r_b.cross(direction)).cross(r_b);
+        let k = body_a.inverse_mass + body_b.inverse_mass + direction.dot(angular_a + angular_b);
+
+        if k > 0.0 { 1.0 / k } else { 0.0 }
+    };
+
+    // Vitesse de rapprochement avant résolution, négative si les corps se rapprochent
+    let approach = normal.dot(body_b.velocity_at(point.point) - body_a.velocity_at(point.point));
+    let restitution = body_a.restitution.max(body_b.restitution);
+    let bounce = if approach < -RESTITUTION_THRESHOLD { -restitution * approach } else { 0.0 };
+    let correction = BAUMGARTE / dt * (point.depth - PENETRATION_SLOP).max(0.0);
+
+    ContactConstraint {
+        a,
+        b,
+        point,
+        tangents: [tangent, bitangent],
+        normal_mass: effective_mass(normal),
+        tangent_masses: [effective_mass(tangent), effective_mass(bitangent)],
+        bias: bounce.max(correction),
+        friction: (body_a.friction * body_b.friction).sqrt(),
+        normal_impulse: 0.0,
+        tangent_impulses: [0.0; 2],
+    }
+}
+
+// Impulsions séquentielles : l'impulsion cumulée est bornée plutôt que
+// chaque impulsion, ce qui converge mieux au fil des passes
+fn solve_contact(bodies: &mut [Body], constraint: &mut ContactConstraint) {
+    let point = constraint.point.point;
+    let normal = constraint.point.normal;
+    let relative_velocity = |bodies: &[Body]| {
+        bodies[constraint.b].velocity_at(point) - bodies[constraint.a].velocity_at(point)
+    };
+
+    let velocity = normal.dot(relative_velocity(bodies));
+    let impulse = (constraint.bias - velocity) * constraint.normal_mass;
+    let total = (constraint.normal_impulse + impulse).max(0.0);
+    let impulse = total - constraint.normal_impulse;
+    constraint.normal_impulse = total;
+    apply_pair_impulse(bodies, constraint.a, constraint.b, normal * impulse, point);
+
+    // Frottement de Coulomb, borné par l'impulsion normale
+    let max_friction = constraint.friction * constraint.normal_impulse;
+    for i in 0..2 {
+        let tangent = constraint.tangents[i];
+        let velocity = tangent.dot(relative_velocity(bodies));
+        let impulse = -velocity * constraint.tangent_masses[i];
+        let total = (constraint.tangent_impulses[i] + impulse).clamp(-max_friction, max_friction);
+        let impulse = total - constraint.tangent_impulses[i];
+        constraint.tangent_impulses[i] = total;
+        apply_pair_impulse(bodies, constraint.a, constraint.b, tangent * impulse, point);
+    }
+}
+
+fn apply_pair_impulse(bodies: &mut [Body], a: usize, b: usize, impulse: Vec3, point: Vec3) {
+    bodies[a].apply_impulse(-impulse, point);
+    bodies[b].apply_impulse(impulse, point);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sol statique de 10 x 1 x 10 dont le dessus est en y = 0
+    fn world_with_floor() -> World {
+        let mut world = World::new();
+        let floor = world.spawn();
+        world.insert(floor, Transform::from_translation(Vec3::new(0.0, -0.5, 0.0)));
+        world.insert(floor, CollisionShape::Box {
+            half_extents: Vec3::new(5.0, 0.5, 5.0),
+        });
+
+        world
+    }
+
+    fn drop_body(world: &mut World, shape: CollisionShape, height: f32) -> Entity {
+        let entity = world.spawn();
+        world.insert(entity, Transform::from_translation(Vec3::new(0.0, height, 0.0)));
+        world.insert(entity, RigidBody::dynamic(1.0).with_restitution(0.3));
+        world.insert(entity, shape);
+        entity
+    }
+
+    fn simulate(physics: &mut Physics, world: &mut World, seconds: f32) {
+        for _ in 0..(seconds * 60.0) as u32 {
+            physics.step(world, 1.0 / 60.0);
+        }
+    }
+
+    #[test]
+    fn box_dropped_on_the_floor_comes_to_rest() {
+        let mut world = world_with_floor();
+        let body = drop_body(&mut world, CollisionShape::Box { half_extents: Vec3::splat(0.5) }, 3.0);
+        let mut physics = Physics::new();
+
+        simulate(&mut physics, &mut world, 3.0);
+
+        let transform = *world.get::<Transform>(body).unwrap();
+        let rigid_body = *world.get::<RigidBody>(body).unwrap();
+
+        // Posée sur le sol, à la pénétration tolérée près, sans avoir basculé
+        // ni beaucoup glissé malgré l'ordre de résolution des contacts
+        let translation = transform.translation;
+        assert!((translation.y - 0.5).abs() < 2.0 * PENETRATION_SLOP, "{translation}");
+        assert!(translation.x.abs() < 1e-2 && translation.z.abs() < 1e-2, "{translation}");
+        assert!(transform.rotation.angle_between(Quat::IDENTITY) < 1e-2);
+        assert!(rigid_body.linear_velocity.length() < 0.05, "{}", rigid_body.linear_velocity);
+        assert!(rigid_body.angular_velocity.length() < 0.05, "{}", rigid_body.angular_velocity);
+
+        // Les quatre coins du bas touchent, la normale va du sol vers la caisse
+        assert_eq!(physics.contacts().len(), 4);
+        for contact in physics.contacts() {
+            assert_eq!(contact.b, body);
+            assert!(contact.normal.abs_diff_eq(Vec3::Y, 1e-4));
+        }
+    }
+
+    #[test]
+    fn sphere_dropped_on_the_floor_comes_to_rest() {
+        let mut world = world_with_floor();
+        let body = drop_body(&mut world, CollisionShape::Sphere { radius: 0.25 }, 2.0);
+        let mut physics = Physics::new();
+
+        simulate(&mut physics, &mut world, 3.0);
+
+        let y = world.get::<Transform>(body).unwrap().translation.y;
+        assert!((y - 0.25).abs() < 2.0 * PENETRATION_SLOP, "{y}");
+        assert!(world.get::<RigidBody>(body).unwrap().linear_velocity.length() < 0.05);
+    }
+
+    #[test]
+    fn bodies_fall_freely_without_contacts() {
+        let mut world = World::new();
+        let body = drop_body(&mut world, CollisionShape::Sphere { radius: 0.5 }, 0.0);
+        world.get_mut::<RigidBody>(body).unwrap().linear_damping = 0.0;
+        let mut physics = Physics::new().with_gravity(Vec3::new(0.0, -10.0, 0.0));
+
+        simulate(&mut physics, &mut world, 1.0);
+
+        // Euler semi-implicite : un peu plus que la chute exacte de 5 m
+        let velocity = world.get::<RigidBody>(body).unwrap().linear_velocity;
+        let y = world.get::<Transform>(body).unwrap().translation.y;
+        assert!(velocity.abs_diff_eq(Vec3::new(0.0, -10.0, 0.0), 1e-3), "{velocity}");
+        assert!((-5.2..-5.0).contains(&y), "{y}");
+        assert!(physics.contacts().is_empty());
+    }
+
+    #[test]
+    fn step_runs_whole_fixed_steps() {
+        let mut world = World::new();
+        let mut physics = Physics::new().with_time_step(0.1);
+
+        assert_eq!(physics.step(&mut world, 0.25), 2);
+        assert_eq!(physics.step(&mut world, 0.05), 1);
+        // Une longue frame est plafonnée
+        assert_eq!(physics.step(&mut world, 10.0), MAX_STEPS_PER_FRAME);
+        assert_eq!(physics.step(&mut world, 0.0), 1);
+    }
+}
@@ -0,0 +1,542 @@
+use glam::{Mat4, Quat, Vec3};
+
+use super::CollisionShape;
+use crate::bounds::Aabb;
+
+// Un sommet à cette distance d'une boîte compte encore comme dedans
+const CONTACT_TOLERANCE: f32 = 0.005;
+
+// Forme déjà mise à l'échelle, placée dans le monde
+#[derive(Copy, Clone, Debug)]
+pub(super) struct ShapePose {
+    pub shape: CollisionShape,
+    pub position: Vec3,
+    pub rotation: Quat,
+}
+
+// La normale va de la première forme vers la seconde
+#[derive(Copy, Clone, Debug)]
+pub(super) struct ContactPoint {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub depth: f32,
+}
+
+impl ShapePose {
+    pub fn aabb(&self) -> Aabb {
+        match self.shape {
+            CollisionShape::Box { half_extents } => Aabb::from_center_extents(Vec3::ZERO, half_extents)
+                .transform(Mat4::from_rotation_translation(self.rotation, self.position)),
+            CollisionShape::Sphere { radius } | CollisionShape::Capsule { radius, .. } => {
+                let (start, end) = self.segment();
+                Aabb::new(start.min(end) - Vec3::splat(radius), start.max(end) + Vec3::splat(radius))
+            }
+        }
+    }
+
+    // Une sphère est une capsule de longueur nulle
+    fn segment(&self) -> (Vec3, Vec3) {
+        let half_height = match self.shape {
+            CollisionShape::Capsule { half_height, .. } => half_height,
+            _ => 0.0,
+        };
+        let axis = self.rotation * Vec3::Y * half_height;
+
+        (self.position - axis, self.position + axis)
+    }
+
+    fn radius(&self) -> f32 {
+        match self.shape {
+            CollisionShape::Sphere { radius } | CollisionShape::Capsule { radius, .. } => radius,
+            CollisionShape::Box { .. } => 0.0,
+        }
+    }
+
+    fn axes(&self) -> [Vec3; 3] {
+        [self.rotation * Vec3::X, self.rotation * Vec3::Y, self.rotation * Vec3::Z]
+    }
+}
+
+pub(super) fn collide(a: &ShapePose, b: &ShapePose, contacts: &mut Vec<ContactPoint>) {
+    match (a.shape, b.shape) {
+        (CollisionShape::Box { half_extents: a_half }, CollisionShape::Box { half_extents: b_half }) => {
+            box_box(a, a_half, b, b_half, contacts)
+        }
+        (CollisionShape::Box { half_extents }, _) => box_capsule(a, half_extents, b, contacts),
+        (_, CollisionShape::Box { half_extents }) => {
+            let start = contacts.len();
+            box_capsule(b, half_extents, a, contacts);
+            for contact in &mut contacts[start..] {
+                contact.normal = -contact.normal;
+            }
+        }
+        _ => capsule_capsule(a, b, contacts),
+    }
+}
+
+fn capsule_capsule(a: &ShapePose, b: &ShapePose, contacts: &mut Vec<ContactPoint>) {
+    let (a_start, a_end) = a.segment();
+    let (b_start, b_end) = b.segment();
+    let (on_a, on_b) = closest_points_on_segments(a_start, a_end, b_start, b_end);
+
+    let delta = on_b - on_a;
+    let distance = delta.length();
+    let radii = a.radius() + b.radius();
+
+    if distance >= radii {
+        return;
+    }
+
+    // Centres confondus, n'importe quelle direction fait l'affaire
+    let normal = if distance > f32::EPSILON { delta / distance } else { Vec3::Y };
+
+    contacts.push(ContactPoint {
+        point: on_a + normal * (a.radius() - (radii - distance) * 0.5),
+        normal,
+        depth: radii - distance,
+    });
+}
+
+// Les deux extrémités sont testées pour qu'une capsule couchée repose sur deux
+// points, plus le point le plus proche de la boîte s'il est entre les deux
+fn box_capsule(a: &ShapePose, half_extents: Vec3, b: &ShapePose, contacts: &mut Vec<ContactPoint>) {
+    let (start, end) = b.segment();
+    let radius = b.radius();
+
+    if let Some(contact) = box_sphere(a, half_extents, start, radius) {
+        contacts.push(contact);
+    }
+
+    if start == end {
+        return;
+    }
+
+    if let Some(contact) = box_sphere(a, half_extents, end, radius) {
+        contacts.push(contact);
+    }
+
+    let t = closest_segment_parameter_to_box(a, half_extents, start, end);
+    if t > 0.05 && t < 0.95 {
+        if let Some(contact) = box_sphere(a, half_extents, start.lerp(end, t), radius) {
+            contacts.push(contact);
+        }
+    }
+}
+
+fn box_sphere(a: &ShapePose, half_extents: Vec3, center: Vec3, radius: f32) -> Option<ContactPoint> {
+    let inverse_rotation = a.rotation.inverse();
+    let local = inverse_rotation * (center - a.position);
+    let clamped = local.clamp(-half_extents, half_extents);
+
+    if local != clamped {
+        let delta = local - clamped;
+        let distance = delta.length();
+        if distance >= radius {
+            return None;
+        }
+
+        return Some(ContactPoint {
+            point: a.position + a.rotation * clamped,
+            normal: a.rotation * (delta / distance),
+            depth: radius - distance,
+        });
+    }
+
+    // Centre dans la boîte : on sort par la face la plus proche
+    let distances = half_extents - local.abs();
+    let axis = if distances.x <= distances.y && distances.x <= distances.z {
+        0
+    } else if distances.y <= distances.z {
+        1
+    } else {
+        2
+    };
+
+    let mut local_normal = Vec3::ZERO;
+    local_normal[axis] = if local[axis] >= 0.0 { 1.0 } else { -1.0 };
+
+    let mut surface = local;
+    surface[axis] = local_normal[axis] * half_extents[axis];
+
+    Some(ContactPoint {
+        point: a.position + a.rotation * surface,
+        normal: a.rotation * local_normal,
+        depth: radius + distances[axis],
+    })
+}
+
+// Quelques allers-retours entre le point le plus proche sur la boîte et
+// celui sur le segment, suffisant pour une boîte convexe
+fn closest_segment_parameter_to_box(a: &ShapePose, half_extents: Vec3, start: Vec3, end: Vec3) -> f32 {
+    let inverse_rotation = a.rotation.inverse();
+    let direction = end - start;
+    let mut t = 0.5;
+
+    for _ in 0..4 {
+        let local = inverse_rotation * (start.lerp(end, t) - a.position);
+        let on_box = a.position + a.rotation * local.clamp(-half_extents, half_extents);
+        t = ((on_box - start).dot(direction) / direction.length_squared()).clamp(0.0, 1.0);
+    }
+
+    t
+}
+
+#[derive(Copy, Clone)]
+enum SeparatingAxis {
+    Face,
+    // Produit vectoriel de l'arête `i` de A et `j` de B
+    Edge(usize, usize),
+}
+
+// Théorème de l'axe séparateur sur les 15 axes. Les contacts sont les sommets
+// de chaque boîte qui sont dans l'autre, ou le point le plus proche entre
+// deux arêtes.
+fn box_box(a: &ShapePose, a_half: Vec3, b: &ShapePose, b_half: Vec3, contacts: &mut Vec<ContactPoint>) {
+    let a_axes = a.axes();
+    let b_axes = b.axes();
+    let offset = b.position - a.position;
+
+    let project = |axis: Vec3, axes: &[Vec3; 3], half: Vec3| {
+        axes[0].dot(axis).abs() * half.x + axes[1].dot(axis).abs() * half.y + axes[2].dot(axis).abs() * half.z
+    };
+
+    // (recouvrement, recouvrement pondéré, normale de A vers B, axe)
+    let mut best: Option<(f32, f32, Vec3, SeparatingAxis)> = None;
+    let mut test_axis = |axis: Vec3, kind: SeparatingAxis| {
+        let overlap = project(axis, &a_axes, a_half) + project(axis, &b_axes, b_half) - offset.dot(axis).abs();
+        if overlap < 0.0 {
+            return false;
+        }
+
+        // Les faces donnent des contacts plus stables, elles sont préférées
+        // à une arête de recouvrement presque égal
+        let weighted = match kind {
+            SeparatingAxis::Face => overlap,
+            SeparatingAxis::Edge(..) => overlap * 1.05 + 0.01,
+        };
+
+        if best.is_none_or(|(_, best_weighted, ..)| weighted < best_weighted) {
+            let normal = if offset.dot(axis) < 0.0 { -axis } else { axis };
+            best = Some((overlap, weighted, normal, kind));
+        }
+
+        true
+    };
+
+    for axis in a_axes.iter().chain(&b_axes) {
+        if !test_axis(*axis, SeparatingAxis::Face) {
+            return;
+        }
+    }
+
+    for (i, a_axis) in a_axes.iter().enumerate() {
+        for (j, b_axis) in b_axes.iter().enumerate() {
+            let axis = a_axis.cross(*b_axis);
+            // Arêtes parallèles, déjà couvert par les faces
+            if axis.length_squared() < 1e-6 {
+                continue;
+            }
+
+            if !test_axis(axis.normalize(), SeparatingAxis::Edge(i, j)) {
+                return;
+            }
+        }
+    }
+
+    let Some((overlap, _, normal, kind)) = best else {
+        return;
+    };
+
+    if let SeparatingAxis::Edge(i, j) = kind {
+        let a_edge = support_edge(a, &a_axes, a_half, normal, i);
+        let b_edge = support_edge(b, &b_axes, b_half, -normal, j);
+        let (on_a, on_b) = closest_points_on_segments(a_edge.0, a_edge.1, b_edge.0, b_edge.1);
+
+        contacts.push(ContactPoint {
+            point: (on_a + on_b) * 0.5,
+            normal,
+            depth: overlap,
+        });
+        return;
+    }
+
+    let start = contacts.len();
+    let a_max = a.position.dot(normal) + project(normal, &a_axes, a_half);
+    let b_min = b.position.dot(normal) - project(normal, &b_axes, b_half);
+
+    for corner in corners(b, b_half) {
+        let depth = a_max - corner.dot(normal);
+        if depth > 0.0 && contains(a, a_half, corner) {
+            contacts.push(ContactPoint { point: corner, normal, depth });
+        }
+    }
+
+    for corner in corners(a, a_half) {
+        let depth = corner.dot(normal) - b_min;
+        if depth > 0.0 && contains(b, b_half, corner) {
+            contacts.push(ContactPoint { point: corner, normal, depth });
+        }
+    }
+
+    // Aucun sommet à l'intérieur, par exemple une arête qui traverse une face
+    if contacts.len() == start {
+        contacts.push(ContactPoint {
+            point: support_point(b, &b_axes, b_half, -normal),
+            normal,
+            depth: overlap,
+        });
+    }
+}
+
+fn corners(pose: &ShapePose, half: Vec3) -> impl Iterator<Item = Vec3> + '_ {
+    (0..8).map(move |i| {
+        let sign = Vec3::new(
+            if i & 1 == 0 { -1.0 } else { 1.0 },
+            if i & 2 == 0 { -1.0 } else { 1.0 },
+            if i & 4 == 0 { -1.0 } else { 1.0 },
+        );
+        pose.position + pose.rotation * (sign * half)
+    })
+}
+
+fn contains(pose: &ShapePose, half: Vec3, point: Vec3) -> bool {
+    let local = pose.rotation.inverse() * (point - pose.position);
+    (local.abs() - half).max_element() <= CONTACT_TOLERANCE
+}
+
+// Sommet le plus loin dans `direction`
+fn support_point(pose: &ShapePose, axes: &[Vec3; 3], half: Vec3, direction: Vec3) -> Vec3 {
+    axes.iter().zip(half.to_array()).fold(pose.position, |point, (axis, half)| {
+        point + *axis * half * axis.dot(direction).signum()
+    })
+}
+
+// Arête parallèle à l'axe `index` qui passe par le sommet le plus loin dans `direction`
+fn support_edge(pose: &ShapePose, axes: &[Vec3; 3], half: Vec3, direction: Vec3, index: usize) -> (Vec3, Vec3) {
+    let corner = support_point(pose, axes, half, direction);
+    let center = corner - axes[index] * half[index] * axes[index].dot(direction).signum();
+    let extent = axes[index] * half[index];
+
+    (center - extent, center + extent)
+}
+
+// Points les plus proches entre les segments [p1, q1] et [p2, q2] (Ericson,
+// Real-Time Collision Detection 5.1.9)
+fn closest_points_on_segments(p1: Vec3, q1: Vec3, p2: Vec3, q2: Vec3) -> (Vec3, Vec3) {
+    let d1 = q1 - p1;
+    let d2 = q2 - p2;
+    let r = p1 - p2;
+    let a = d1.length_squared();
+    let e = d2.length_squared();
+    let f = d2.dot(r);
+
+    if a <= f32::EPSILON && e <= f32::EPSILON {
+        return (p1, p2);
+    }
+
+    let (s, t) = if a <= f32::EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(r);
+
+        if e <= f32::EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(d2);
+            let denominator = a * e - b * b;
+
+            // Segments parallèles, n'importe quel s convient
+            let s = if denominator > f32::EPSILON {
+                ((b * f - c * e) / denominator).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+
+            let t = (b * s + f) / e;
+            if t < 0.0 {
+                ((-c / a).clamp(0.0, 1.0), 0.0)
+            } else if t > 1.0 {
+                (((b - c) / a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+
+    (p1 + d1 * s, p2 + d2 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn pose(shape: CollisionShape, position: Vec3) -> ShapePose {
+        ShapePose {
+            shape,
+            position,
+            rotation: Quat::IDENTITY,
+        }
+    }
+
+    fn cuboid(half_extents: Vec3, position: Vec3) -> ShapePose {
+        pose(CollisionShape::Box { half_extents }, position)
+    }
+
+    fn capsule(position: Vec3, rotation: Quat) -> ShapePose {
+        ShapePose {
+            rotation,
+            ..pose(CollisionShape::Capsule { radius: 0.5, half_height: 1.0 }, position)
+        }
+    }
+
+    fn contacts(a: &ShapePose, b: &ShapePose) -> Vec<ContactPoint> {
+        let mut contacts = Vec::new();
+        collide(a, b, &mut contacts);
+        contacts
+    }
+
+    #[test]
+    fn box_resting_on_box() {
+        let floor = cuboid(Vec3::new(2.0, 0.5, 2.0), Vec3::ZERO);
+        let crate_box = cuboid(Vec3::splat(0.5), Vec3::new(0.0, 0.95, 0.0));
+
+        // Les quatre sommets du bas de la caisse sont dans le sol
+        let points = contacts(&floor, &crate_box);
+        assert_eq!(points.len(), 4);
+
+        for contact in &points {
+            assert!(contact.normal.abs_diff_eq(Vec3::Y, 1e-5));
+            assert!((contact.depth - 0.05).abs() < 1e-5);
+            assert!((contact.point.y - 0.45).abs() < 1e-5);
+        }
+
+        // Dans l'autre sens, la normale est inversée
+        for contact in contacts(&crate_box, &floor) {
+            assert!(contact.normal.abs_diff_eq(Vec3::NEG_Y, 1e-5));
+        }
+    }
+
+    #[test]
+    fn separated_boxes() {
+        let a = cuboid(Vec3::splat(0.5), Vec3::ZERO);
+        let b = ShapePose {
+            rotation: Quat::from_rotation_y(0.7),
+            ..cuboid(Vec3::splat(0.5), Vec3::new(1.3, 0.2, 0.0))
+        };
+
+        assert!(contacts(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn sphere_outside_box() {
+        let box_pose = cuboid(Vec3::ONE, Vec3::ZERO);
+
+        let contact = box_sphere(&box_pose, Vec3::ONE, Vec3::new(0.2, 1.3, 0.0), 0.5).unwrap();
+        assert!(contact.normal.abs_diff_eq(Vec3::Y, 1e-5));
+        assert!(contact.point.abs_diff_eq(Vec3::new(0.2, 1.0, 0.0), 1e-5));
+        assert!((contact.depth - 0.2).abs() < 1e-5);
+
+        // Près d'un coin, la normale va du coin vers le centre de la sphère
+        let contact = box_sphere(&box_pose, Vec3::ONE, Vec3::new(1.2, 1.2, 0.0), 0.5).unwrap();
+        assert!(contact.normal.abs_diff_eq(Vec3::new(1.0, 1.0, 0.0).normalize(), 1e-5));
+
+        assert!(box_sphere(&box_pose, Vec3::ONE, Vec3::new(0.0, 1.6, 0.0), 0.5).is_none());
+    }
+
+    #[test]
+    fn sphere_inside_box() {
+        let box_pose = cuboid(Vec3::ONE, Vec3::ZERO);
+
+        // Le centre sort par la face la plus proche
+        let contact = box_sphere(&box_pose, Vec3::ONE, Vec3::new(0.0, 0.0, -0.8), 0.1).unwrap();
+        assert!(contact.normal.abs_diff_eq(Vec3::NEG_Z, 1e-5));
+        assert!(contact.point.abs_diff_eq(Vec3::new(0.0, 0.0, -1.0), 1e-5));
+        assert!((contact.depth - 0.3).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rotated_box_and_swapped_sphere() {
+        let box_pose = ShapePose {
+            rotation: Quat::from_rotation_z(FRAC_PI_2 * 0.5),
+            ..cuboid(Vec3::ONE, Vec3::ZERO)
+        };
+        let sphere = pose(CollisionShape::Sphere { radius: 0.5 }, Vec3::new(1.0, 1.0, 0.0));
+
+        // La face du haut à droite est tournée de 45°
+        let points = contacts(&sphere, &box_pose);
+        assert_eq!(points.len(), 1);
+        assert!(points[0].normal.abs_diff_eq(Vec3::new(-1.0, -1.0, 0.0).normalize(), 1e-5));
+        assert!((points[0].depth - (1.5 - 2.0f32.sqrt())).abs() < 1e-5);
+    }
+
+    #[test]
+    fn parallel_capsules() {
+        let a = capsule(Vec3::ZERO, Quat::IDENTITY);
+        let b = capsule(Vec3::new(0.8, 0.5, 0.0), Quat::IDENTITY);
+
+        let points = contacts(&a, &b);
+        assert_eq!(points.len(), 1);
+        assert!(points[0].normal.abs_diff_eq(Vec3::X, 1e-5));
+        assert!((points[0].depth - 0.2).abs() < 1e-5);
+        assert!((points[0].point.x - 0.4).abs() < 1e-5);
+
+        assert!(contacts(&a, &capsule(Vec3::new(1.1, 0.0, 0.0), Quat::IDENTITY)).is_empty());
+    }
+
+    #[test]
+    fn crossing_capsules() {
+        let a = capsule(Vec3::ZERO, Quat::IDENTITY);
+        // Couchée le long de X, devant la première
+        let b = capsule(Vec3::new(0.3, 0.4, 0.9), Quat::from_rotation_z(FRAC_PI_2));
+
+        let points = contacts(&a, &b);
+        assert_eq!(points.len(), 1);
+        assert!(points[0].normal.abs_diff_eq(Vec3::Z, 1e-5));
+        assert!((points[0].depth - 0.1).abs() < 1e-5);
+        assert!(points[0].point.abs_diff_eq(Vec3::new(0.0, 0.4, 0.45), 1e-5));
+    }
+
+    #[test]
+    fn capsule_lying_on_a_box() {
+        let floor = cuboid(Vec3::new(3.0, 0.5, 3.0), Vec3::ZERO);
+        let lying = capsule(Vec3::new(0.0, 0.95, 0.0), Quat::from_rotation_z(FRAC_PI_2));
+
+        // Les deux extrémités et le milieu
+        let points = contacts(&floor, &lying);
+        assert_eq!(points.len(), 3);
+
+        for contact in &points {
+            assert!(contact.normal.abs_diff_eq(Vec3::Y, 1e-5));
+            assert!((contact.depth - 0.05).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn closest_points_between_segments() {
+        // Segments croisés
+        let (a, b) = closest_points_on_segments(
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.5, -1.0, 1.0),
+            Vec3::new(0.5, 1.0, 1.0),
+        );
+        assert!(a.abs_diff_eq(Vec3::new(0.5, 0.0, 0.0), 1e-5));
+        assert!(b.abs_diff_eq(Vec3::new(0.5, 0.0, 1.0), 1e-5));
+
+        // Parallèles : la distance est la bonne même si le point est arbitraire
+        let (a, b) = closest_points_on_segments(Vec3::ZERO, Vec3::X, Vec3::new(0.5, 1.0, 0.0), Vec3::new(2.0, 1.0, 0.0));
+        assert!((a.distance(b) - 1.0).abs() < 1e-5);
+
+        // Extrémités les plus proches quand les segments ne se font pas face
+        let (a, b) = closest_points_on_segments(Vec3::ZERO, Vec3::X, Vec3::new(2.0, 1.0, 0.0), Vec3::new(3.0, 2.0, 0.0));
+        assert!(a.abs_diff_eq(Vec3::X, 1e-5));
+        assert!(b.abs_diff_eq(Vec3::new(2.0, 1.0, 0.0), 1e-5));
+
+        // Segments réduits à un point
+        let (a, b) = closest_points_on_segments(Vec3::ONE, Vec3::ONE, Vec3::ZERO, Vec3::Y * 2.0);
+        assert_eq!(a, Vec3::ONE);
+        assert!(b.abs_diff_eq(Vec3::Y, 1e-5));
+    }
+}
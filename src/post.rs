@@ -0,0 +1,694 @@
+use std::any::Any;
+
+use wgpu::{include_spirv, util::DeviceExt};
+
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+pub struct RenderTarget {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl RenderTarget {
+    pub fn new(
+        device: &wgpu::Device,
+        label: &str,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let width = width.max(1);
+        let height = height.max(1);
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self {
+            texture,
+            view,
+            width,
+            height,
+        }
+    }
+}
+
+pub struct PostContext<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub sampler: &'a wgpu::Sampler,
+    pub fullscreen_triangle: &'a wgpu::Buffer,
+    pub width: u32,
+    pub height: u32,
+}
+
+pub trait PostEffect: Any {
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    fn run(
+        &mut self,
+        ctx: &PostContext,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+    );
+}
+
+// Le premier effet lit la scène, les suivants lisent et écrivent dans deux cibles
+// HDR en ping-pong, puis une dernière passe recopie le résultat dans la cible
+// finale (la swapchain en général).
+pub struct PostProcessChain {
+    effects: Vec<Box<dyn PostEffect>>,
+    targets: [RenderTarget; 2],
+    blit: FullscreenPipeline,
+    sampler: wgpu::Sampler,
+    fullscreen_triangle: wgpu::Buffer,
+    width: u32,
+    height: u32,
+}
+
+impl PostProcessChain {
+    pub fn new(
+        device: &wgpu::Device,
+        output_format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Process Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        // Un seul triangle qui recouvre tout l'écran
+        let fullscreen_triangle = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Fullscreen Triangle Buffer"),
+            contents: bytemuck::cast_slice(&[[-1.0f32, -1.0], [3.0, -1.0], [-1.0, 3.0]]),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let blit = FullscreenPipeline::new(
+            device,
+            "Blit",
+            include_spirv!(concat!(env!("OUT_DIR"), "/blit.spv")),
+            output_format,
+            FullscreenBindings::default(),
+        );
+
+        Self {
+            effects: Vec::new(),
+            targets: Self::create_targets(device, width, height),
+            blit,
+            sampler,
+            fullscreen_triangle,
+            width,
+            height,
+        }
+    }
+
+    pub fn with(mut self, effect: impl PostEffect) -> Self {
+        self.push(effect);
+        self
+    }
+
+    pub fn push(&mut self, effect: impl PostEffect) {
+        self.effects.push(Box::new(effect));
+    }
+
+    pub fn effect<T: PostEffect>(&self) -> Option<&T> {
+        self.effects
+            .iter()
+            .find_map(|effect| (effect.as_ref() as &dyn Any).downcast_ref())
+    }
+
+    pub fn effect_mut<T: PostEffect>(&mut self) -> Option<&mut T> {
+        self.effects
+            .iter_mut()
+            .find_map(|effect| (effect.as_mut() as &mut dyn Any).downcast_mut())
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.targets = Self::create_targets(device, width, height);
+    }
+
+    pub fn run(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+    ) {
+        let ctx = PostContext {
+            device,
+            queue,
+            sampler: &self.sampler,
+            fullscreen_triangle: &self.fullscreen_triangle,
+            width: self.width,
+            height: self.height,
+        };
+
+        let mut current = input;
+        let mut next_target = 0;
+
+        for effect in self.effects.iter_mut().filter(|effect| effect.enabled()) {
+            let target = &self.targets[next_target].view;
+            effect.run(&ctx, encoder, current, target);
+            current = target;
+            next_target = 1 - next_target;
+        }
+
+        let bind_group = self.blit.bind_group(&ctx, current, None, None);
+        self.blit.draw(&ctx, encoder, "Blit Pass", output, &bind_group);
+    }
+
+    fn create_targets(device: &wgpu::Device, width: u32, height: u32) -> [RenderTarget; 2] {
+        [
+            RenderTarget::new(device, "Post Process Target A", width, height, HDR_FORMAT),
+            RenderTarget::new(device, "Post Process Target B", width, height, HDR_FORMAT),
+        ]
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+pub struct FullscreenBindings {
+    pub params: bool,
+    pub extra_texture: bool,
+}
+
+// Pipeline plein écran générique : binding 0 = texture d'entrée, 1 = sampler,
+// 2 = paramètres et 3 = seconde texture si demandés.
+pub struct FullscreenPipeline {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl FullscreenPipeline {
+    pub fn new(
+        device: &wgpu::Device,
+        label: &str,
+        shader_desc: wgpu::ShaderModuleDescriptor,
+        format: wgpu::TextureFormat,
+        bindings: FullscreenBindings,
+    ) -> Self {
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
+        let mut entries = vec![
+            texture_entry(0),
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ];
+
+        if bindings.params {
+            entries.push(wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            });
+        }
+
+        if bindings.extra_texture {
+            entries.push(texture_entry(3));
+        }
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(label),
+            entries: &entries,
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(label),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(shader_desc);
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &[wgpu::VertexAttribute {
+                        offset: 0,
+                        shader_location: 0,
+                        format: wgpu::VertexFormat::Float32x2,
+                    }],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+        }
+    }
+
+    pub fn bind_group(
+        &self,
+        ctx: &PostContext,
+        input: &wgpu::TextureView,
+        params: Option<&wgpu::Buffer>,
+        extra_texture: Option<&wgpu::TextureView>,
+    ) -> wgpu::BindGroup {
+        let mut entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(input),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(ctx.sampler),
+            },
+        ];
+
+        if let Some(params) = params {
+            entries.push(wgpu::BindGroupEntry {
+                binding: 2,
+                resource: params.as_entire_binding(),
+            });
+        }
+
+        if let Some(extra_texture) = extra_texture {
+            entries.push(wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(extra_texture),
+            });
+        }
+
+        ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.bind_group_layout,
+            entries: &entries,
+        })
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &PostContext,
+        encoder: &mut wgpu::CommandEncoder,
+        label: &str,
+        output: &wgpu::TextureView,
+        bind_group: &wgpu::BindGroup,
+    ) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(label),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.set_vertex_buffer(0, ctx.fullscreen_triangle.slice(..));
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+fn create_params_buffer<T: bytemuck::Pod>(device: &wgpu::Device, label: &str) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: std::mem::size_of::<T>() as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+pub struct Bloom {
+    pub enabled: bool,
+    pub threshold: f32,
+    pub knee: f32,
+    pub intensity: f32,
+    pub blur_passes: u32,
+
+    threshold_pipeline: FullscreenPipeline,
+    blur_pipeline: FullscreenPipeline,
+    composite_pipeline: FullscreenPipeline,
+    threshold_params: wgpu::Buffer,
+    blur_params: [wgpu::Buffer; 2],
+    composite_params: wgpu::Buffer,
+    targets: Option<[RenderTarget; 2]>,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct BloomThresholdUniform {
+    threshold: f32,
+    knee: f32,
+    _pad: [u32; 2],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct BloomBlurUniform {
+    direction: [f32; 2],
+    _pad: [u32; 2],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct BloomCompositeUniform {
+    intensity: f32,
+    _pad: [u32; 3],
+}
+
+impl Bloom {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let with_params = FullscreenBindings {
+            params: true,
+            extra_texture: false,
+        };
+
+        Self {
+            enabled: true,
+            threshold: 1.0,
+            knee: 0.5,
+            intensity: 0.8,
+            blur_passes: 3,
+            threshold_pipeline: FullscreenPipeline::new(
+                device,
+                "Bloom Threshold",
+                include_spirv!(concat!(env!("OUT_DIR"), "/bloom_threshold.spv")),
+                HDR_FORMAT,
+                with_params,
+            ),
+            blur_pipeline: FullscreenPipeline::new(
+                device,
+                "Bloom Blur",
+                include_spirv!(concat!(env!("OUT_DIR"), "/bloom_blur.spv")),
+                HDR_FORMAT,
+                with_params,
+            ),
+            composite_pipeline: FullscreenPipeline::new(
+                device,
+                "Bloom Composite",
+                include_spirv!(concat!(env!("OUT_DIR"), "/bloom_composite.spv")),
+                HDR_FORMAT,
+                FullscreenBindings {
+                    params: true,
+                    extra_texture: true,
+                },
+            ),
+            threshold_params: create_params_buffer::<BloomThresholdUniform>(device, "Bloom Threshold Params"),
+            blur_params: [
+                create_params_buffer::<BloomBlurUniform>(device, "Bloom Blur Params H"),
+                create_params_buffer::<BloomBlurUniform>(device, "Bloom Blur Params V"),
+            ],
+            composite_params: create_params_buffer::<BloomCompositeUniform>(device, "Bloom Composite Params"),
+            targets: None,
+        }
+    }
+}
+
+impl PostEffect for Bloom {
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn run(
+        &mut self,
+        ctx: &PostContext,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+    ) {
+        // Le flou se fait en demi-résolution, c'est moins cher et ça élargit le halo
+        let width = (ctx.width / 2).max(1);
+        let height = (ctx.height / 2).max(1);
+
+        if !matches!(&self.targets, Some([target, _]) if target.width == width && target.height == height) {
+            self.targets = Some([
+                RenderTarget::new(ctx.device, "Bloom Target A", width, height, HDR_FORMAT),
+                RenderTarget::new(ctx.device, "Bloom Target B", width, height, HDR_FORMAT),
+            ]);
+        }
+
+        let [bright, scratch] = self.targets.as_ref().unwrap();
+
+        ctx.queue.write_buffer(
+            &self.threshold_params,
+            0,
+            bytemuck::cast_slice(&[BloomThresholdUniform {
+                threshold: self.threshold,
+                knee: self.knee.max(0.0001),
+                _pad: [0; 2],
+            }]),
+        );
+
+        ctx.queue.write_buffer(
+            &self.blur_params[0],
+            0,
+            bytemuck::cast_slice(&[BloomBlurUniform {
+                direction: [1.0 / width as f32, 0.0],
+                _pad: [0; 2],
+            }]),
+        );
+
+        ctx.queue.write_buffer(
+            &self.blur_params[1],
+            0,
+            bytemuck::cast_slice(&[BloomBlurUniform {
+                direction: [0.0, 1.0 / height as f32],
+                _pad: [0; 2],
+            }]),
+        );
+
+        ctx.queue.write_buffer(
+            &self.composite_params,
+            0,
+            bytemuck::cast_slice(&[BloomCompositeUniform {
+                intensity: self.intensity,
+                _pad: [0; 3],
+            }]),
+        );
+
+        let bind_group = self.threshold_pipeline.bind_group(ctx, input, Some(&self.threshold_params), None);
+        self.threshold_pipeline.draw(ctx, encoder, "Bloom Threshold Pass", &bright.view, &bind_group);
+
+        let horizontal = self.blur_pipeline.bind_group(ctx, &bright.view, Some(&self.blur_params[0]), None);
+        let vertical = self.blur_pipeline.bind_group(ctx, &scratch.view, Some(&self.blur_params[1]), None);
+
+        for _ in 0..self.blur_passes {
+            self.blur_pipeline.draw(ctx, encoder, "Bloom Blur Pass", &scratch.view, &horizontal);
+            self.blur_pipeline.draw(ctx, encoder, "Bloom Blur Pass", &bright.view, &vertical);
+        }
+
+        let bind_group = self.composite_pipeline.bind_group(
+            ctx,
+            input,
+            Some(&self.composite_params),
+            Some(&bright.view),
+        );
+
+        self.composite_pipeline.draw(ctx, encoder, "Bloom Composite Pass", output, &bind_group);
+    }
+}
+
+pub struct Fxaa {
+    pub enabled: bool,
+    pub span_max: f32,
+    pub reduce_mul: f32,
+    pub reduce_min: f32,
+
+    pipeline: FullscreenPipeline,
+    params: wgpu::Buffer,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct FxaaUniform {
+    texel_size: [f32; 2],
+    span_max: f32,
+    reduce_mul: f32,
+    reduce_min: f32,
+    _pad: [u32; 3],
+}
+
+impl Fxaa {
+    pub fn new(device: &wgpu::Device) -> Self {
+        Self {
+            enabled: true,
+            span_max: 8.0,
+            reduce_mul: 1.0 / 8.0,
+            reduce_min: 1.0 / 128.0,
+            pipeline: FullscreenPipeline::new(
+                device,
+                "FXAA",
+                include_spirv!(concat!(env!("OUT_DIR"), "/fxaa.spv")),
+                HDR_FORMAT,
+                FullscreenBindings {
+                    params: true,
+                    extra_texture: false,
+                },
+            ),
+            params: create_params_buffer::<FxaaUniform>(device, "FXAA Params"),
+        }
+    }
+}
+
+impl PostEffect for Fxaa {
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn run(
+        &mut self,
+        ctx: &PostContext,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+    ) {
+        ctx.queue.write_buffer(
+            &self.params,
+            0,
+            bytemuck::cast_slice(&[FxaaUniform {
+                texel_size: [1.0 / ctx.width.max(1) as f32, 1.0 / ctx.height.max(1) as f32],
+                span_max: self.span_max,
+                reduce_mul: self.reduce_mul,
+                reduce_min: self.reduce_min,
+                _pad: [0; 3],
+            }]),
+        );
+
+        let bind_group = self.pipeline.bind_group(ctx, input, Some(&self.params), None);
+        self.pipeline.draw(ctx, encoder, "FXAA Pass", output, &bind_group);
+    }
+}
+
+pub struct Vignette {
+    pub enabled: bool,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub radius: f32,
+    pub smoothness: f32,
+
+    pipeline: FullscreenPipeline,
+    params: wgpu::Buffer,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct VignetteUniform {
+    color: [f32; 3],
+    intensity: f32,
+    radius: f32,
+    smoothness: f32,
+    _pad: [u32; 2],
+}
+
+impl Vignette {
+    pub fn new(device: &wgpu::Device) -> Self {
+        Self {
+            enabled: true,
+            color: [0.0, 0.0, 0.0],
+            intensity: 0.6,
+            radius: 0.9,
+            smoothness: 0.5,
+            pipeline: FullscreenPipeline::new(
+                device,
+                "Vignette",
+                include_spirv!(concat!(env!("OUT_DIR"), "/vignette.spv")),
+                HDR_FORMAT,
+                FullscreenBindings {
+                    params: true,
+                    extra_texture: false,
+                },
+            ),
+            params: create_params_buffer::<VignetteUniform>(device, "Vignette Params"),
+        }
+    }
+}
+
+impl PostEffect for Vignette {
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn run(
+        &mut self,
+        ctx: &PostContext,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+    ) {
+        ctx.queue.write_buffer(
+            &self.params,
+            0,
+            bytemuck::cast_slice(&[VignetteUniform {
+                color: self.color,
+                intensity: self.intensity,
+                radius: self.radius,
+                smoothness: self.smoothness,
+                _pad: [0; 2],
+            }]),
+        );
+
+        let bind_group = self.pipeline.bind_group(ctx, input, Some(&self.params), None);
+        self.pipeline.draw(ctx, encoder, "Vignette Pass", output, &bind_group);
+    }
+}
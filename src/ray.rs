@@ -0,0 +1,222 @@
+use glam::{Mat4, Vec3};
+
+use crate::bounds::Aabb;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3,
+    // Normalisée, les distances sont donc en unités du monde
+    pub direction: Vec3,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RayHit {
+    pub distance: f32,
+    pub normal: Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + self.direction * distance
+    }
+
+    // La direction n'est pas renormalisée : les distances le long du rayon
+    // transformé restent celles du rayon d'origine
+    pub fn transform(&self, matrix: Mat4) -> Self {
+        Self {
+            origin: matrix.transform_point3(self.origin),
+            direction: matrix.transform_vector3(self.direction),
+        }
+    }
+
+    // Distance d'entrée dans la boîte (méthode des slabs), 0 si l'origine est dedans
+    pub fn aabb_distance(&self, aabb: &Aabb) -> Option<f32> {
+        let (t_min, _) = self.slabs(aabb)?;
+        Some(t_min.max_element().max(0.0))
+    }
+
+    // Depuis l'intérieur de la boîte, c'est la face de sortie qui est touchée
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<RayHit> {
+        let (t_min, t_max) = self.slabs(aabb)?;
+        let near = t_min.max_element();
+
+        let (distance, faces, sign) = if near >= 0.0 {
+            (near, t_min, -1.0)
+        } else {
+            (t_max.min_element(), t_max, 1.0)
+        };
+
+        let axis = (0..3).find(|&axis| faces[axis] == distance).unwrap_or(0);
+        let mut normal = Vec3::ZERO;
+        normal[axis] = sign * self.direction[axis].signum();
+
+        Some(RayHit { distance, normal })
+    }
+
+    // Boîte `local_aabb` placée dans le monde par `model_matrix`
+    pub fn intersect_box(&self, local_aabb: &Aabb, model_matrix: Mat4) -> Option<RayHit> {
+        let inverse = model_matrix.inverse();
+        let hit = self.transform(inverse).intersect_aabb(local_aabb)?;
+
+        Some(RayHit {
+            distance: hit.distance,
+            normal: transform_normal(hit.normal, inverse),
+        })
+    }
+
+    // Möller-Trumbore, les deux faces sont touchées. La normale est celle de
+    // la face avant, sommets dans le sens trigonométrique.
+    pub fn intersect_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<RayHit> {
+        let edge1 = b - a;
+        let edge2 = c - a;
+        let p = self.direction.cross(edge2);
+        let determinant = edge1.dot(p);
+
+        if determinant.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let inverse_determinant = 1.0 / determinant;
+        let to_origin = self.origin - a;
+        let u = to_origin.dot(p) * inverse_determinant;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = to_origin.cross(edge1);
+        let v = self.direction.dot(q) * inverse_determinant;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let distance = edge2.dot(q) * inverse_determinant;
+        (distance >= 0.0).then(|| RayHit {
+            distance,
+            normal: edge1.cross(edge2).normalize(),
+        })
+    }
+
+    // Distances d'entrée et de sortie des trois paires de plans, `None` si
+    // le rayon manque la boîte ou si elle est derrière lui
+    fn slabs(&self, aabb: &Aabb) -> Option<(Vec3, Vec3)> {
+        if aabb.is_empty() {
+            return None;
+        }
+
+        let inverse_direction = self.direction.recip();
+        let t1 = (aabb.min - self.origin) * inverse_direction;
+        let t2 = (aabb.max - self.origin) * inverse_direction;
+        let t_min = t1.min(t2);
+        let t_max = t1.max(t2);
+
+        let near = t_min.max_element();
+        let far = t_max.min_element();
+
+        (near <= far && far >= 0.0).then_some((t_min, t_max))
+    }
+}
+
+// Une normale se transforme par la transposée de l'inverse
+pub(crate) fn transform_normal(normal: Vec3, inverse_matrix: Mat4) -> Vec3 {
+    inverse_matrix.transpose().transform_vector3(normal).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Quat;
+
+    use super::*;
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Vec3::splat(-1.0), Vec3::ONE)
+    }
+
+    #[test]
+    fn aabb_hit_from_outside() {
+        let hits = [
+            (Ray::new(Vec3::new(-5.0, 0.2, 0.0), Vec3::X), 4.0, Vec3::NEG_X),
+            (Ray::new(Vec3::new(0.5, 3.0, -0.5), Vec3::NEG_Y), 2.0, Vec3::Y),
+            (Ray::new(Vec3::new(0.0, 0.0, 4.0), Vec3::new(0.1, 0.0, -1.0)), 3.0 * 1.01f32.sqrt(), Vec3::Z),
+        ];
+
+        for (ray, distance, normal) in hits {
+            let hit = ray.intersect_aabb(&unit_box()).unwrap();
+            assert!((hit.distance - distance).abs() < 1e-5, "{ray:?}");
+            assert_eq!(hit.normal, normal, "{ray:?}");
+            assert_eq!(ray.aabb_distance(&unit_box()), Some(hit.distance));
+        }
+    }
+
+    #[test]
+    fn aabb_hit_from_inside() {
+        let ray = Ray::new(Vec3::new(0.0, 0.5, 0.0), Vec3::NEG_Y);
+        let hit = ray.intersect_aabb(&unit_box()).unwrap();
+
+        // La face de sortie, avec sa normale vers l'extérieur
+        assert!((hit.distance - 1.5).abs() < 1e-6);
+        assert_eq!(hit.normal, Vec3::NEG_Y);
+        assert_eq!(ray.aabb_distance(&unit_box()), Some(0.0));
+    }
+
+    #[test]
+    fn aabb_misses() {
+        let misses = [
+            Ray::new(Vec3::new(-5.0, 1.5, 0.0), Vec3::X),
+            Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::NEG_X),
+            Ray::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 0.0)),
+        ];
+
+        for ray in misses {
+            assert_eq!(ray.intersect_aabb(&unit_box()), None, "{ray:?}");
+        }
+
+        assert_eq!(Ray::new(Vec3::ZERO, Vec3::X).intersect_aabb(&Aabb::EMPTY), None);
+    }
+
+    #[test]
+    fn box_placed_by_a_matrix() {
+        let matrix = Mat4::from_scale_rotation_translation(
+            Vec3::new(2.0, 1.0, 1.0),
+            Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            Vec3::new(0.0, 0.0, -10.0),
+        );
+
+        // Tournée de 90°, la boîte fait 2 de profondeur le long de Z
+        let hit = Ray::new(Vec3::ZERO, Vec3::NEG_Z).intersect_box(&unit_box(), matrix).unwrap();
+        assert!((hit.distance - 8.0).abs() < 1e-4);
+        assert!(hit.normal.abs_diff_eq(Vec3::Z, 1e-5));
+    }
+
+    #[test]
+    fn triangle_hits_and_misses() {
+        let (a, b, c) = (Vec3::ZERO, Vec3::X, Vec3::Y);
+
+        let hit = Ray::new(Vec3::new(0.2, 0.2, 1.0), Vec3::NEG_Z).intersect_triangle(a, b, c).unwrap();
+        assert!((hit.distance - 1.0).abs() < 1e-6);
+        assert_eq!(hit.normal, Vec3::Z);
+
+        // À côté, parallèle, ou triangle derrière l'origine
+        assert_eq!(Ray::new(Vec3::new(0.6, 0.6, 1.0), Vec3::NEG_Z).intersect_triangle(a, b, c), None);
+        assert_eq!(Ray::new(Vec3::new(-0.1, 0.2, 1.0), Vec3::NEG_Z).intersect_triangle(a, b, c), None);
+        assert_eq!(Ray::new(Vec3::new(0.2, 0.2, 1.0), Vec3::X).intersect_triangle(a, b, c), None);
+        assert_eq!(Ray::new(Vec3::new(0.2, 0.2, 1.0), Vec3::Z).intersect_triangle(a, b, c), None);
+    }
+
+    #[test]
+    fn triangle_hit_from_behind() {
+        let hit = Ray::new(Vec3::new(0.2, 0.2, -2.0), Vec3::Z)
+            .intersect_triangle(Vec3::ZERO, Vec3::X, Vec3::Y)
+            .unwrap();
+
+        // La normale reste celle de la face avant
+        assert!((hit.distance - 2.0).abs() < 1e-6);
+        assert_eq!(hit.normal, Vec3::Z);
+    }
+}
@@ -0,0 +1,333 @@
+use glam::{vec3, Mat4, Vec3};
+use wgpu::include_spirv;
+
+pub const SHADOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// Ordre des faces d'une cubemap : +X, -X, +Y, -Y, +Z, -Z
+const CUBE_FACES: [(Vec3, Vec3); 6] = [
+    (Vec3::X, Vec3::NEG_Y),
+    (Vec3::NEG_X, Vec3::NEG_Y),
+    (Vec3::Y, Vec3::Z),
+    (Vec3::NEG_Y, Vec3::NEG_Z),
+    (Vec3::Z, Vec3::NEG_Y),
+    (Vec3::NEG_Z, Vec3::NEG_Y),
+];
+
+// Ombres omnidirectionnelles pour une lumière ponctuelle : la scène est rendue
+// six fois depuis la lumière dans une cubemap de profondeur, qui est ensuite
+// échantillonnée avec un PCF dans le shader éclairé.
+pub struct PointShadowMap {
+    pub bias: f32,
+    pub pcf_radius: f32,
+    pub near: f32,
+    pub far: f32,
+
+    size: u32,
+    face_views: Vec<wgpu::TextureView>,
+    face_buffers: Vec<wgpu::Buffer>,
+    face_bind_groups: Vec<wgpu::BindGroup>,
+    pipeline: wgpu::RenderPipeline,
+    params_buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowFaceUniform {
+    view_proj_matrix: [f32; 16],
+    light_pos: [f32; 3],
+    far: f32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowParamsUniform {
+    far: f32,
+    bias: f32,
+    pcf_radius: f32,
+    light_index: u32,
+}
+
+impl PointShadowMap {
+    pub const NO_CASTER: u32 = u32::MAX;
+
+    // `vertex_layouts` doit fournir la position en location 0 et la matrice
+    // modèle de l'instance en locations 4 à 7, comme pour le shader de base.
+    pub fn new(device: &wgpu::Device, size: u32, vertex_layouts: &[wgpu::VertexBufferLayout]) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Point Shadow Map"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: SHADOW_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let face_views = (0..6)
+            .map(|face| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Point Shadow Map Face"),
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_array_layer: face,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        let cube_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Point Shadow Map Cube"),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Point Shadow Map Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let face_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Shadow Face Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+        let face_buffers: Vec<_> = (0..6)
+            .map(|_| {
+                device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Shadow Face Buffer"),
+                    size: std::mem::size_of::<ShadowFaceUniform>() as wgpu::BufferAddress,
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                })
+            })
+            .collect();
+
+        let face_bind_groups = face_buffers
+            .iter()
+            .map(|buffer| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Shadow Face Bind Group"),
+                    layout: &face_bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                })
+            })
+            .collect();
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shadow Params Buffer"),
+            size: std::mem::size_of::<ShadowParamsUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadow Map Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::Cube,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::NonFiltering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow Map Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&cube_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: params_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadow Pipeline Layout"),
+            bind_group_layouts: &[&face_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = device.create_shader_module(include_spirv!(concat!(env!("OUT_DIR"), "/point_shadow.spv")));
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Point Shadow Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: vertex_layouts,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                // Les faces sont retournées verticalement, pas de culling pour
+                // ne pas avoir à inverser l'ordre des sommets
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: SHADOW_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            bias: 0.005,
+            pcf_radius: 0.05,
+            near: 0.05,
+            far: 50.0,
+            size,
+            face_views,
+            face_buffers,
+            face_bind_groups,
+            pipeline,
+            params_buffer,
+            bind_group_layout,
+            bind_group,
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    pub fn face_view_proj(&self, light_pos: Vec3, face: usize) -> Mat4 {
+        let (dir, up) = CUBE_FACES[face];
+        let view = Mat4::look_at_rh(light_pos, light_pos + dir, up);
+        let proj = Mat4::perspective_rh(90.0f32.to_radians(), 1.0, self.near, self.far);
+
+        // wgpu range la première ligne en haut de l'image, contrairement à la
+        // convention OpenGL pour laquelle ces vecteurs "up" ont été choisis
+        Mat4::from_scale(vec3(1.0, -1.0, 1.0)) * proj * view
+    }
+
+    // `light_index` est la position de la lumière dans la `LightList`, le shader
+    // éclairé n'applique l'ombre qu'à cette lumière-là
+    pub fn update(&self, queue: &wgpu::Queue, light_pos: Vec3, light_index: usize) {
+        for (face, buffer) in self.face_buffers.iter().enumerate() {
+            let face_uniform = ShadowFaceUniform {
+                view_proj_matrix: self.face_view_proj(light_pos, face).to_cols_array(),
+                light_pos: light_pos.into(),
+                far: self.far,
+            };
+
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&[face_uniform]));
+        }
+
+        self.write_params(queue, light_index as u32);
+    }
+
+    // Sans lumière qui projette l'ombre, aucun indice de la `LightList` ne
+    // correspond et le shader n'applique plus d'ombre
+    pub fn disable(&self, queue: &wgpu::Queue) {
+        self.write_params(queue, Self::NO_CASTER);
+    }
+
+    fn write_params(&self, queue: &wgpu::Queue, light_index: u32) {
+        let params_uniform = ShadowParamsUniform {
+            far: self.far,
+            bias: self.bias,
+            pcf_radius: self.pcf_radius,
+            light_index,
+        };
+
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::cast_slice(&[params_uniform]));
+    }
+
+    // Commence la passe d'une face, il ne reste qu'à binder les buffers et dessiner
+    pub fn begin_face_pass<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        face: usize,
+    ) -> wgpu::RenderPass<'a> {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Point Shadow Pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.face_views[face],
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.face_bind_groups[face], &[]);
+        render_pass
+    }
+}
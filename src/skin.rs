@@ -0,0 +1,318 @@
+use glam::{Mat4, Quat, Vec3};
+use wgpu::util::DeviceExt;
+
+use crate::{
+    anim::{find_segment, hermite, Interpolation, Keyframe},
+    transform::Transform,
+};
+
+// Doit rester égal à MaxJointCount dans common.nzsl
+pub const MAX_JOINTS: usize = 64;
+
+// Attributs de skinning, dans un vertex buffer à part pour que `VertexData`
+// reste le même pour tous les meshes. Les poids doivent avoir une somme de 1.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SkinVertex {
+    pub joints: [u16; 4],
+    pub weights: [f32; 4],
+}
+
+impl SkinVertex {
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SkinVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 9,
+                    format: wgpu::VertexFormat::Uint16x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[u16; 4]>() as wgpu::BufferAddress,
+                    shader_location: 10,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
+
+pub struct GpuSkin {
+    pub vertex_buffer: wgpu::Buffer,
+}
+
+impl GpuSkin {
+    // `skin` doit avoir un élément par sommet du mesh
+    pub fn new(skin: &[SkinVertex], device: &wgpu::Device) -> Self {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Skin Vertex Buffer"),
+            contents: bytemuck::cast_slice(skin),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        Self { vertex_buffer }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Joint {
+    pub name: Option<String>,
+    pub parent: Option<usize>,
+    // Transformation locale quand aucune animation ne touche l'articulation
+    pub rest: Transform,
+    // Passe de l'espace du mesh à celui de l'articulation dans la pose de liaison
+    pub inverse_bind_matrix: Mat4,
+}
+
+#[derive(Clone, Debug)]
+pub struct Skeleton {
+    joints: Vec<Joint>,
+    // Les parents avant leurs enfants, quel que soit l'ordre de `joints`
+    order: Vec<usize>,
+}
+
+impl Skeleton {
+    pub fn new(joints: Vec<Joint>) -> Self {
+        let mut children = vec![Vec::new(); joints.len()];
+        let mut order = Vec::with_capacity(joints.len());
+
+        for (index, joint) in joints.iter().enumerate() {
+            match joint.parent.filter(|&parent| parent < joints.len()) {
+                Some(parent) => children[parent].push(index),
+                None => order.push(index),
+            }
+        }
+
+        let mut cursor = 0;
+        while cursor < order.len() {
+            order.extend_from_slice(&children[order[cursor]]);
+            cursor += 1;
+        }
+
+        Self { joints, order }
+    }
+
+    pub fn joints(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    pub fn find_joint(&self, name: &str) -> Option<usize> {
+        self.joints.iter().position(|joint| joint.name.as_deref() == Some(name))
+    }
+
+    pub fn rest_pose(&self) -> Pose {
+        Pose {
+            joints: self.joints.iter().map(|joint| joint.rest).collect(),
+        }
+    }
+
+    // Matrices de chaque articulation dans l'espace du mesh
+    pub fn world_matrices(&self, pose: &Pose) -> Vec<Mat4> {
+        let mut matrices = vec![Mat4::IDENTITY; self.joints.len()];
+
+        for &index in &self.order {
+            let local = pose.joints[index].matrix();
+
+            matrices[index] = match self.joints[index].parent {
+                Some(parent) => matrices[parent] * local,
+                None => local,
+            };
+        }
+
+        matrices
+    }
+
+    // Matrices à envoyer au shader : elles amènent un sommet de la pose de liaison
+    // à sa position dans `pose`
+    pub fn palette(&self, pose: &Pose) -> Vec<Mat4> {
+        self.world_matrices(pose)
+            .into_iter()
+            .zip(&self.joints)
+            .map(|(world, joint)| world * joint.inverse_bind_matrix)
+            .collect()
+    }
+
+    // Moyenne des clips pondérée par leur poids, la pose de repos si aucun
+    // n'a de poids positif
+    pub fn evaluate(&self, layers: &[ClipLayer]) -> Pose {
+        let mut pose = self.rest_pose();
+        let mut total_weight = 0.0;
+
+        for layer in layers.iter().filter(|layer| layer.weight > 0.0) {
+            let mut layer_pose = self.rest_pose();
+            layer.clip.sample(layer.time, &mut layer_pose);
+
+            pose = if total_weight == 0.0 {
+                layer_pose
+            } else {
+                pose.blend(&layer_pose, layer.weight / (total_weight + layer.weight))
+            };
+            total_weight += layer.weight;
+        }
+
+        pose
+    }
+}
+
+// Transformations locales de chaque articulation, dans l'ordre du squelette
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pose {
+    pub joints: Vec<Transform>,
+}
+
+impl Pose {
+    pub fn blend(&self, other: &Pose, t: f32) -> Pose {
+        Pose {
+            joints: self
+                .joints
+                .iter()
+                .zip(&other.joints)
+                .map(|(a, b)| a.lerp(b, t))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ChannelValues {
+    Translation(Vec<Vec3>),
+    Rotation(Vec<Quat>),
+    Scale(Vec<Vec3>),
+}
+
+#[derive(Clone, Debug)]
+pub struct Channel {
+    pub joint: usize,
+    pub interpolation: Interpolation,
+    pub times: Vec<f32>,
+    // En cubique, trois valeurs par clé comme dans glTF : tangente entrante,
+    // valeur et tangente sortante
+    pub values: ChannelValues,
+}
+
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    pub name: Option<String>,
+    pub duration: f32,
+    pub channels: Vec<Channel>,
+}
+
+impl AnimationClip {
+    // La durée est celle de la dernière clé
+    pub fn new(name: Option<String>, channels: Vec<Channel>) -> Self {
+        let duration = channels
+            .iter()
+            .filter_map(|channel| channel.times.last().copied())
+            .fold(0.0, f32::max);
+
+        Self {
+            name,
+            duration,
+            channels,
+        }
+    }
+
+    // Ramène `time` dans la durée du clip pour le jouer en boucle
+    pub fn wrap_time(&self, time: f32) -> f32 {
+        if self.duration > 0.0 {
+            time.rem_euclid(self.duration)
+        } else {
+            0.0
+        }
+    }
+
+    // Écrase les articulations animées de `pose`, les autres ne changent pas.
+    // Avant la première clé et après la dernière, la valeur est maintenue.
+    pub fn sample(&self, time: f32, pose: &mut Pose) {
+        for channel in &self.channels {
+            let Some(joint) = pose.joints.get_mut(channel.joint) else {
+                continue;
+            };
+
+            let times = &channel.times;
+            let interpolation = channel.interpolation;
+
+            match &channel.values {
+                ChannelValues::Translation(values) => {
+                    if let Some(value) = sample_keyframes(times, values, interpolation, time) {
+                        joint.translation = value;
+                    }
+                }
+                ChannelValues::Rotation(values) => {
+                    if let Some(value) = sample_keyframes(times, values, interpolation, time) {
+                        joint.rotation = value.normalize();
+                    }
+                }
+                ChannelValues::Scale(values) => {
+                    if let Some(value) = sample_keyframes(times, values, interpolation, time) {
+                        joint.scale = value;
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct ClipLayer<'a> {
+    pub clip: &'a AnimationClip,
+    pub time: f32,
+    pub weight: f32,
+}
+
+// En cubique, les valeurs vont par trois : tangente entrante, valeur et tangente sortante
+fn sample_keyframes<T: Keyframe>(times: &[f32], values: &[T], interpolation: Interpolation, time: f32) -> Option<T> {
+    let stride = if interpolation == Interpolation::Cubic { 3 } else { 1 };
+    let key_count = times.len().min(values.len() / stride);
+    let value = |key: usize| values[key * stride + stride / 2];
+
+    let (previous, next, t) = find_segment(key_count, |key| times[key], time)?;
+
+    Some(match interpolation {
+        _ if previous == next => value(previous),
+        Interpolation::Step => value(previous),
+        Interpolation::Linear => value(previous).interpolate(value(next), t),
+        Interpolation::Cubic => {
+            let dt = times[next] - times[previous];
+            let out_tangent = values[previous * 3 + 2] * dt;
+            let in_tangent = values[next * 3] * dt;
+
+            hermite(value(previous), out_tangent, value(next), in_tangent, t)
+        }
+    })
+}
+
+// Palette des matrices d'articulations, dans un uniform buffer pour rester
+// compatible avec WebGL2
+pub struct JointPalette {
+    buffer: wgpu::Buffer,
+}
+
+impl JointPalette {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Joint Palette Buffer"),
+            size: (MAX_JOINTS * std::mem::size_of::<[f32; 16]>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self { buffer }
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    // Les articulations au-delà de MAX_JOINTS sont ignorées
+    pub fn update(&self, queue: &wgpu::Queue, matrices: &[Mat4]) {
+        let data: Vec<[f32; 16]> = matrices
+            .iter()
+            .take(MAX_JOINTS)
+            .map(|matrix| matrix.to_cols_array())
+            .collect();
+
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&data));
+    }
+}
@@ -0,0 +1,161 @@
+use std::path::Path;
+
+use image::{imageops::FilterType, RgbaImage};
+
+pub use image::ImageError;
+
+// Les textures de couleur (albedo) sont en sRGB, les données (normal maps,
+// roughness...) doivent rester linéaires
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    Linear,
+}
+
+impl ColorSpace {
+    fn format(self) -> wgpu::TextureFormat {
+        match self {
+            Self::Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+            Self::Linear => wgpu::TextureFormat::Rgba8Unorm,
+        }
+    }
+}
+
+pub struct Texture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Texture {
+    // PNG ou JPEG, le format est deviné à partir du contenu
+    pub fn load(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: impl AsRef<Path>,
+        color_space: ColorSpace,
+    ) -> Result<Self, ImageError> {
+        let path = path.as_ref();
+        let image = image::open(path)?.to_rgba8();
+
+        Ok(Self::from_image(device, queue, &path.to_string_lossy(), &image, color_space))
+    }
+
+    pub fn from_bytes(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        bytes: &[u8],
+        color_space: ColorSpace,
+    ) -> Result<Self, ImageError> {
+        let image = image::load_from_memory(bytes)?.to_rgba8();
+
+        Ok(Self::from_image(device, queue, label, &image, color_space))
+    }
+
+    // `pixels` en RGBA8, ligne par ligne, comme les textures glTF
+    pub fn from_rgba8(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        color_space: ColorSpace,
+    ) -> Self {
+        let image = RgbaImage::from_raw(width, height, pixels.to_vec()).expect("pixel buffer too small");
+
+        Self::from_image(device, queue, label, &image, color_space)
+    }
+
+    // Texture 1x1, utile comme valeur neutre quand un matériau n'a pas de texture
+    pub fn solid(device: &wgpu::Device, queue: &wgpu::Queue, label: &str, rgba: [u8; 4], color_space: ColorSpace) -> Self {
+        Self::from_image(device, queue, label, &RgbaImage::from_pixel(1, 1, image::Rgba(rgba)), color_space)
+    }
+
+    // Toute la chaîne de mipmaps est générée sur le CPU avant l'envoi, ce qui
+    // marche aussi en WebGL2 où on ne peut pas faire de rendu dans une texture sRGB
+    pub fn from_image(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        image: &RgbaImage,
+        color_space: ColorSpace,
+    ) -> Self {
+        let (width, height) = image.dimensions();
+        let mip_level_count = mip_level_count(width, height);
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: color_space.format(),
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        let mut mip = image.clone();
+
+        for mip_level in 0..mip_level_count {
+            if mip_level > 0 {
+                let mip_width = (width >> mip_level).max(1);
+                let mip_height = (height >> mip_level).max(1);
+                mip = image::imageops::resize(&mip, mip_width, mip_height, FilterType::Triangle);
+            }
+
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &mip,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * mip.width()),
+                    rows_per_image: Some(mip.height()),
+                },
+                wgpu::Extent3d {
+                    width: mip.width(),
+                    height: mip.height(),
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some(label),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            address_mode_w: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            texture,
+            view,
+            sampler,
+            width,
+            height,
+        }
+    }
+}
+
+fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}